@@ -128,8 +128,7 @@ impl MainState
     {
         let mut grid = Grid::new(400, 400);
         
-        grid.array[0] = 1;
-        grid.array[1] = 1;
+        grid.blit(&Grid::new_filled(2, 1, 1), [0, 0], BlendMode::Replace);
         *grid.index_mut([100, 100]).unwrap() = 1;
         *grid.index_mut([100, 101]).unwrap() = 1;
         
@@ -0,0 +1,358 @@
+use mint::Point2;
+
+use crate::{Automaton, Boundary, Grid, GridDim, IsRules, RuleTable};
+
+/// Dead/alive cells packed 64 to a `u64`, with every row starting on a fresh word.
+///
+/// Bit `j` of word `i` in a row is the cell at column `64 * i + j`. The bits past
+/// `width` in the last word of a row are always kept clear so they read as dead.
+#[derive(Clone, Debug)]
+pub struct BitGrid
+{
+    dim: GridDim,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl std::ops::Deref for BitGrid
+{
+    type Target = GridDim;
+
+    fn deref(&self) -> &Self::Target
+    {
+        &self.dim
+    }
+}
+
+impl BitGrid
+{
+    pub fn new(width: isize, height: isize) -> Self
+    {
+        assert!(width > 0);
+        assert!(height > 0);
+
+        let dim = GridDim { width, height, boundary: Boundary::default() };
+        let words_per_row = (width as usize).div_ceil(64);
+
+        BitGrid
+        {
+            dim,
+            words_per_row,
+            words: vec![0; words_per_row * height as usize],
+        }
+    }
+
+    /// The size can't change, since the packed rows are laid out for it, but the boundary can
+    pub fn set_boundary(&mut self, boundary: Boundary)
+    {
+        self.dim.boundary = boundary;
+    }
+
+    pub fn words_per_row(&self) -> usize
+    {
+        self.words_per_row
+    }
+
+    /// The packed words of row `y`
+    pub fn row(&self, y: isize) -> &[u64]
+    {
+        let start = y as usize * self.words_per_row;
+        &self.words[start..start + self.words_per_row]
+    }
+
+    fn row_mut(&mut self, y: isize) -> &mut [u64]
+    {
+        let start = y as usize * self.words_per_row;
+        &mut self.words[start..start + self.words_per_row]
+    }
+
+    /// word index and bit of `p`, if it is inside the grid
+    fn locate(&self, p: impl Into<Point2<isize>>) -> Option<(usize, u64)>
+    {
+        let p = p.into();
+        if p.x < 0 || p.x >= self.width || p.y < 0 || p.y >= self.height
+        {
+            return None;
+        }
+
+        let word = p.y as usize * self.words_per_row + p.x as usize / 64;
+        Some((word, 1 << (p.x % 64)))
+    }
+
+    pub fn get(&self, p: impl Into<Point2<isize>>) -> Option<bool>
+    {
+        self.locate(p).map(|(word, bit)| self.words[word] & bit != 0)
+    }
+
+    /// Set the cell at `p`, returning its previous state or `None` if `p` is outside the grid
+    pub fn set(&mut self, p: impl Into<Point2<isize>>, alive: bool) -> Option<bool>
+    {
+        let (word, bit) = self.locate(p)?;
+        let was = self.words[word] & bit != 0;
+
+        if alive
+        {
+            self.words[word] |= bit;
+        }
+        else
+        {
+            self.words[word] &= !bit;
+        }
+
+        Some(was)
+    }
+
+    /// The cell `p` refers to under the boundary, which may be past an edge
+    fn get_through_boundary(&self, p: impl Into<Point2<isize>>) -> bool
+    {
+        match self.resolve(p)
+        {
+            Some(p) => self.get(p).unwrap(),
+            None => self.boundary == Boundary::Alive,
+        }
+    }
+
+    /// The packed words of a row just above or below the grid
+    fn ghost_row(&self, y: isize) -> Vec<u64>
+    {
+        let mut row = vec![0; self.words_per_row];
+        for x in 0..self.width
+        {
+            if self.get_through_boundary([x, y])
+            {
+                row[x as usize / 64] |= 1 << (x % 64);
+            }
+        }
+
+        row
+    }
+
+    pub fn count_alive(&self) -> usize
+    {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Mask of the bits in the last word of a row that are real cells
+    fn last_word_mask(&self) -> u64
+    {
+        match self.width % 64
+        {
+            0 => u64::MAX,
+            rem => (1 << rem) - 1,
+        }
+    }
+
+    /// Write the next generation under `rules` into `out`, resizing it if needed.
+    ///
+    /// Neighbour counts for 64 cells are summed at once with bitwise adders, and cells
+    /// outside the grid are decided by the boundary.
+    pub fn step_into(&self, rules: &impl IsRules, out: &mut BitGrid)
+    {
+        if out.words.len() != self.words.len() || out.words_per_row != self.words_per_row
+        {
+            out.words = vec![0; self.words.len()];
+            out.words_per_row = self.words_per_row;
+        }
+        out.dim = self.dim;
+
+        let table = RuleTable::two_state(rules);
+        let birth = table.birth.moore_bits();
+        let survive = table.survive.moore_bits();
+
+        let top = self.ghost_row(-1);
+        let bottom = self.ghost_row(self.height);
+        let last_mask = self.last_word_mask();
+        let last_bit = ((self.width - 1) % 64) as u32;
+
+        // the cells just past the west and east ends of row y
+        let edges = |y: isize| -> (u64, u64)
+        {
+            (
+                self.get_through_boundary([-1, y]) as u64,
+                (self.get_through_boundary([self.width, y]) as u64) << last_bit,
+            )
+        };
+
+        for y in 0..self.height
+        {
+            let up = if y > 0 { self.row(y - 1) } else { &top };
+            let mid = self.row(y);
+            let down = if y + 1 < self.height { self.row(y + 1) } else { &bottom };
+
+            let (up_edges, mid_edges, down_edges) = (edges(y - 1), edges(y), edges(y + 1));
+
+            let out_row = out.row_mut(y);
+            for i in 0..self.words_per_row
+            {
+                let mut counts = Counts::default();
+                for (row, row_edges) in [(up, up_edges), (down, down_edges)]
+                {
+                    let (west, east) = west_east(row, i, row_edges);
+                    counts.add(west);
+                    counts.add(row[i]);
+                    counts.add(east);
+                }
+                let (west, east) = west_east(mid, i, mid_edges);
+                counts.add(west);
+                counts.add(east);
+
+                let alive = mid[i];
+                let mut next =
+                    (alive & counts.matching(survive)) |
+                    (!alive & counts.matching(birth));
+
+                if i + 1 == self.words_per_row
+                {
+                    next &= last_mask;
+                }
+
+                out_row[i] = next;
+            }
+        }
+    }
+}
+
+/// Words holding the west and east neighbour of each cell in word `i` of `row`.
+/// `edges` are the bits shifted in past the first and last cell of the row.
+fn west_east(row: &[u64], i: usize, edges: (u64, u64)) -> (u64, u64)
+{
+    let w = row[i];
+    let from_prev = if i > 0 { row[i - 1] >> 63 } else { edges.0 };
+    let from_next = if i + 1 < row.len() { row[i + 1] << 63 } else { edges.1 };
+
+    ((w << 1) | from_prev, (w >> 1) | from_next)
+}
+
+/// A 4-bit counter per lane, stored one bit-plane per word
+#[derive(Default, Clone, Copy)]
+struct Counts([u64; 4]);
+
+impl Counts
+{
+    /// ripple-carry add a single bit into every lane
+    fn add(&mut self, bits: u64)
+    {
+        let mut carry = bits;
+        for plane in &mut self.0
+        {
+            let next_carry = *plane & carry;
+            *plane ^= carry;
+            carry = next_carry;
+        }
+    }
+
+    fn equal_to(&self, n: u8) -> u64
+    {
+        self.0
+        .iter()
+        .enumerate()
+        .fold(u64::MAX, |acc, (b, &plane)|
+        {
+            acc & if n >> b & 1 == 1 { plane } else { !plane }
+        })
+    }
+
+    /// lanes whose count is set in `mask`
+    fn matching(&self, mask: u16) -> u64
+    {
+        (0..=8)
+        .filter(|n| mask >> n & 1 == 1)
+        .fold(0, |acc, n| acc | self.equal_to(n))
+    }
+}
+
+impl From<&Grid> for BitGrid
+{
+    fn from(grid: &Grid) -> Self
+    {
+        let mut bits = BitGrid::new(grid.width, grid.height);
+        bits.set_boundary(grid.boundary);
+        for y in 0..grid.height
+        {
+            for x in 0..grid.width
+            {
+                if *grid.index([x, y]).unwrap() > 0
+                {
+                    bits.set([x, y], true);
+                }
+            }
+        }
+
+        bits
+    }
+}
+
+impl From<&BitGrid> for Grid
+{
+    fn from(bits: &BitGrid) -> Self
+    {
+        let mut grid = Grid::new(bits.width, bits.height).with_boundary(bits.boundary);
+        for y in 0..bits.height
+        {
+            for x in 0..bits.width
+            {
+                *grid.index_mut([x, y]).unwrap() = bits.get([x, y]).unwrap() as u8;
+            }
+        }
+
+        grid
+    }
+}
+
+impl<R> Automaton<R, BitGrid>
+where
+    R: IsRules
+{
+    pub fn set_boundary(&mut self, boundary: Boundary)
+    {
+        self.grid.set_boundary(boundary);
+        self.other_grid.set_boundary(boundary);
+    }
+
+    /// Step every cell at once, 64 to a word
+    pub fn step(&mut self)
+    {
+        self.grid.step_into(&self.rules, &mut self.other_grid);
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+}
+
+impl_stepper!(<R: IsRules> Automaton<R, BitGrid>);
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{tests::soup, ConstRules};
+
+    #[test]
+    fn steps_like_a_grid_under_every_boundary()
+    {
+        // 70 columns leaves a part-filled word at the end of each row
+        let grid = soup(70, 70, 70, 1);
+        for boundary in [Boundary::Dead, Boundary::Alive, Boundary::Wrap, Boundary::Mirror, Boundary::Klein]
+        {
+            let mut plain = Automaton::new(grid.clone().with_boundary(boundary), ConstRules::new([3], [1, 2, 3, 4, 5]));
+            let mut bits = Automaton::new(BitGrid::from(&plain.grid), ConstRules::new([3], [1, 2, 3, 4, 5]));
+
+            for _ in 0..10
+            {
+                plain.step();
+                bits.step();
+                assert_eq!(Grid::from(&bits.grid).as_slice(), plain.grid.as_slice(), "{:?}", boundary);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_through_a_grid()
+    {
+        let grid = soup(130, 130, 100, 2);
+        let bits = BitGrid::from(&grid);
+
+        assert_eq!(bits.count_alive(), grid.as_slice().iter().filter(|&&v| v > 0).count());
+        assert_eq!(Grid::from(&bits).as_slice(), grid.as_slice());
+        assert_eq!(bits.get([130, 0]), None);
+    }
+}
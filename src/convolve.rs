@@ -0,0 +1,180 @@
+use std::ops::{Add, Mul};
+
+use mint::Point2;
+
+use crate::{Boundary, Grid, GridDim};
+
+/// Weights laid over a cell and its surroundings by `convolve`,
+/// centred on the middle cell of `weights` (rounding down and right)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "K: serde::Serialize + PartialEq", deserialize = "K: serde::Deserialize<'de> + Clone")))]
+pub struct Kernel<K = i32>
+{
+    pub weights: Grid<K>,
+}
+
+impl<K> Kernel<K>
+{
+    pub fn new(weights: Grid<K>) -> Self
+    {
+        Kernel { weights }
+    }
+
+    /// Offset from the centre and weight of every non-zero weight
+    pub fn taps(&self) -> impl Iterator<Item = (Point2<isize>, &K)>
+    where
+        K: Default + PartialEq
+    {
+        let (cx, cy) = (self.weights.width / 2, self.weights.height / 2);
+
+        self.weights
+        .cells()
+        .filter(|(_, w)| **w != K::default())
+        .map(move |(p, w)| (Point2 { x: p.x - cx, y: p.y - cy }, w))
+    }
+
+    /// The `(2 * radius + 1)` square around a cell without the cell itself, weighted 1.
+    /// A radius of 1 counts the same 8 neighbours as `sum_neighbors`.
+    pub fn moore(radius: isize) -> Self
+    where
+        K: Clone + From<u8>
+    {
+        Kernel::from_fn(radius, |x, y| (x, y) != (0, 0))
+    }
+
+    /// Cells within `radius` cityblock steps of a cell, without the cell itself, weighted 1
+    pub fn von_neumann(radius: isize) -> Self
+    where
+        K: Clone + From<u8>
+    {
+        Kernel::from_fn(radius, |x, y| (x, y) != (0, 0) && x.abs() + y.abs() <= radius)
+    }
+
+    /// a square kernel weighted 1 where `include` holds, for offsets from the centre
+    fn from_fn(radius: isize, include: impl Fn(isize, isize) -> bool) -> Self
+    where
+        K: Clone + From<u8>
+    {
+        assert!(radius >= 0);
+
+        let side = 2 * radius + 1;
+        let weights = (0..side)
+            .flat_map(|y| (0..side).map(move |x| (x - radius, y - radius)))
+            .map(|(x, y)| K::from(include(x, y) as u8))
+            .collect();
+
+        Kernel::new(Grid::from_vec(side, side, weights))
+    }
+}
+
+impl<T> Grid<T>
+{
+    /// Weighted sum of each cell's surroundings, with cells past the edges decided by the grid's boundary
+    pub fn convolve<K>(&self, kernel: &Kernel<K>) -> Grid<K>
+    where
+        T: Copy + Into<K>,
+        K: Copy + Default + PartialEq + From<u8> + Add<Output = K> + Mul<Output = K>
+    {
+        self.convolve_with_boundary(kernel, self.boundary)
+    }
+
+    /// Weighted sum of each cell's surroundings, laying `kernel` over the cell as is (without flipping it).
+    ///
+    /// Past the edges cells follow `boundary`: `Dead` reads as 0, `Alive` as 1,
+    /// and the others wrap or reflect back into the grid.
+    pub fn convolve_with_boundary<K>(&self, kernel: &Kernel<K>, boundary: Boundary) -> Grid<K>
+    where
+        T: Copy + Into<K>,
+        K: Copy + Default + PartialEq + From<u8> + Add<Output = K> + Mul<Output = K>
+    {
+        let dim = GridDim { boundary, ..self.dim };
+        let outside = K::from((boundary == Boundary::Alive) as u8);
+        let taps: Vec<_> = kernel.taps().map(|(o, &w)| (o, w)).collect();
+
+        let array = self
+            .points()
+            .map(|p|
+            {
+                taps.iter().fold(K::default(), |acc, &(o, w)|
+                {
+                    let v = match dim.resolve([p.x + o.x, p.y + o.y])
+                    {
+                        Some(q) => (*self.index(q).unwrap()).into(),
+                        None => outside,
+                    };
+                    acc + w * v
+                })
+            })
+            .collect();
+
+        Grid::from_vec(self.width, self.height, array).with_boundary(self.boundary)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// the value at `(x, y)` under `boundary`, worked out without `resolve`
+    fn read(grid: &Grid, boundary: Boundary, x: isize, y: isize) -> i32
+    {
+        let (w, h) = (grid.width, grid.height);
+        let reflect = |v: isize, len: isize| if v < 0 { -1 - v } else if v >= len { 2 * len - 1 - v } else { v };
+
+        let (x, y) = match boundary
+        {
+            _ if grid.dim().contains([x, y]) => (x, y),
+            Boundary::Dead => return 0,
+            Boundary::Alive => return 1,
+            Boundary::Wrap => (x.rem_euclid(w), y.rem_euclid(h)),
+            Boundary::Mirror => (reflect(x, w), reflect(y, h)),
+            // crossing the top or bottom once flips the row
+            Boundary::Klein if (0..h).contains(&y) => (x.rem_euclid(w), y),
+            Boundary::Klein => ((w - 1 - x).rem_euclid(w), y.rem_euclid(h)),
+        };
+        *grid.index([x, y]).unwrap() as i32
+    }
+
+    #[test]
+    fn matches_a_naive_sum_under_every_boundary()
+    {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(10);
+        let grid = Grid::from_vec(9, 7, (0..63).map(|_| rng.gen_range(0..4u8)).collect());
+        // lopsided, with zero and negative weights
+        let kernel = Kernel::new(Grid::from_vec(5, 3, (0..15).map(|_| rng.gen_range(-3..=3)).collect()));
+
+        for boundary in [Boundary::Dead, Boundary::Alive, Boundary::Wrap, Boundary::Mirror, Boundary::Klein]
+        {
+            let sums = grid.convolve_with_boundary(&kernel, boundary);
+            for p in grid.points()
+            {
+                let mut sum = 0;
+                for ky in 0..3
+                {
+                    for kx in 0..5
+                    {
+                        sum += kernel.weights.index([kx, ky]).unwrap() * read(&grid, boundary, p.x + kx - 2, p.y + ky - 1);
+                    }
+                }
+                assert_eq!(*sums.index(p).unwrap(), sum, "{:?} at {:?}", boundary, p);
+            }
+        }
+    }
+
+    #[test]
+    fn moore_and_von_neumann_kernels()
+    {
+        assert_eq!(Kernel::<u32>::moore(1).taps().count(), 8);
+        assert_eq!(Kernel::<u32>::moore(2).taps().count(), 24);
+        assert_eq!(Kernel::<u32>::von_neumann(2).taps().count(), 12);
+
+        // a radius 1 Moore kernel counts what sum_neighbors does
+        let grid = crate::tests::soup(12, 12, 12, 11).with_boundary(Boundary::Alive);
+        let counts = grid.convolve(&Kernel::<u32>::moore(1));
+        assert!(grid.points().all(|p| *counts.index(p).unwrap() == grid.sum_neighbors(p) as u32));
+    }
+}
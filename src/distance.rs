@@ -0,0 +1,234 @@
+use crate::{Boundary, Grid};
+
+/// Stands in for "no wall in this line" in the Euclidean transform,
+/// big enough to never win but small enough to keep the parabola maths finite
+const FAR: f64 = 1e20;
+
+/// Squared distance to the nearest sample for one row or column,
+/// as the lower envelope of parabolas (Felzenszwalb & Huttenlocher)
+fn squared_line(f: &[f64]) -> Vec<f64>
+{
+    let n = f.len();
+    let mut v = vec![0; n];
+    let mut z = vec![0.0; n + 1];
+    let mut k = 0;
+    z[0] = f64::NEG_INFINITY;
+    z[1] = f64::INFINITY;
+
+    // where the parabolas rooted at q and p cross
+    let intersect = |q: usize, p: usize| ((f[q] + (q * q) as f64) - (f[p] + (p * p) as f64)) / (2.0 * (q as f64 - p as f64));
+
+    for q in 1..n
+    {
+        let mut s = intersect(q, v[k]);
+        while s <= z[k]
+        {
+            k -= 1;
+            s = intersect(q, v[k]);
+        }
+
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = f64::INFINITY;
+    }
+
+    k = 0;
+    (0..n)
+    .map(|q|
+    {
+        while z[k + 1] < q as f64
+        {
+            k += 1;
+        }
+        let d = q as f64 - v[k] as f64;
+        d * d + f[v[k]]
+    })
+    .collect()
+}
+
+impl Grid
+{
+    /// Run `f` on the grid with a frame of walls around it when the boundary is `Alive`,
+    /// so the edges count as walls, then cut the frame back off
+    fn with_walled_edges<T: Clone>(&self, f: impl Fn(&Grid) -> Grid<T>) -> Grid<T>
+    {
+        match self.boundary
+        {
+            Boundary::Alive =>
+            {
+                let padded = self.pad(1, 1);
+                f(&padded).crop([1, 1], self.width, self.height).unwrap().with_boundary(self.boundary)
+            },
+            _ => f(self),
+        }
+    }
+
+    /// two raster passes propagating distances from walls, exact for cityblock
+    /// and, with `diagonals`, for chebyshev
+    fn chamfer(&self, diagonals: bool) -> Grid<u32>
+    {
+        let mut d = self.map(|&v| if v > 0 { 0 } else { u32::MAX });
+        let (w, h) = (self.width, self.height);
+
+        let forward: &[(isize, isize)] = if diagonals { &[(-1, -1), (0, -1), (1, -1), (-1, 0)] } else { &[(0, -1), (-1, 0)] };
+        let passes = [
+            (forward.to_vec(), false),
+            (forward.iter().map(|&(dx, dy)| (-dx, -dy)).collect(), true),
+        ];
+
+        for (offsets, backward) in passes
+        {
+            for i in 0..w * h
+            {
+                let i = if backward { w * h - 1 - i } else { i };
+                let (x, y) = (i % w, i / w);
+
+                let nearest = offsets
+                    .iter()
+                    .filter_map(|&(dx, dy)| d.index([x + dx, y + dy]).copied())
+                    .min()
+                    .unwrap_or(u32::MAX)
+                    .saturating_add(1);
+
+                let cell = d.index_mut([x, y]).unwrap();
+                *cell = (*cell).min(nearest);
+            }
+        }
+
+        d
+    }
+
+    /// Cityblock distance from every cell to the nearest wall, `0` on walls
+    /// and `u32::MAX` everywhere if there are none.
+    ///
+    /// With an `Alive` boundary the cells past the edges count as walls too, other boundaries are ignored.
+    pub fn distance_transform_cityblock(&self) -> Grid<u32>
+    {
+        self.with_walled_edges(|g| g.chamfer(false))
+    }
+
+    /// Chebyshev distance from every cell to the nearest wall, like `distance_transform_cityblock`
+    pub fn distance_transform_chebyshev(&self) -> Grid<u32>
+    {
+        self.with_walled_edges(|g| g.chamfer(true))
+    }
+
+    /// Exact straight-line distance from every cell to the nearest wall,
+    /// `0` on walls and infinite everywhere if there are none
+    pub fn distance_transform_euclidean(&self) -> Grid<f32>
+    {
+        self.with_walled_edges(|g|
+        {
+            let (w, h) = (g.width as usize, g.height as usize);
+            let mut d: Vec<f64> = g.as_slice().iter().map(|&v| if v > 0 { 0.0 } else { FAR }).collect();
+
+            for x in 0..w
+            {
+                let column: Vec<f64> = (0..h).map(|y| d[x + y * w]).collect();
+                for (y, v) in squared_line(&column).into_iter().enumerate()
+                {
+                    d[x + y * w] = v;
+                }
+            }
+            for row in d.chunks_mut(w)
+            {
+                let squared = squared_line(row);
+                row.copy_from_slice(&squared);
+            }
+
+            let array = d.into_iter().map(|v| if v >= FAR { f32::INFINITY } else { v.sqrt() as f32 }).collect();
+            Grid::from_vec(g.width, g.height, array).with_boundary(g.boundary)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::tests::soup;
+
+    /// Every wall's offset from `(x, y)`, counting the frame past the edges when the boundary is `Alive`
+    fn wall_offsets(g: &Grid, x: isize, y: isize) -> Vec<(isize, isize)>
+    {
+        let frame = if g.boundary == Boundary::Alive { 1 } else { 0 };
+        let mut offsets = vec![];
+        for wy in -frame..g.height + frame
+        {
+            for wx in -frame..g.width + frame
+            {
+                if g.index([wx, wy]).is_none_or(|&v| v > 0)
+                {
+                    offsets.push((wx - x, wy - y));
+                }
+            }
+        }
+
+        offsets
+    }
+
+    fn check(g: &Grid)
+    {
+        let cityblock = g.distance_transform_cityblock();
+        let chebyshev = g.distance_transform_chebyshev();
+        let euclidean = g.distance_transform_euclidean();
+
+        for y in 0..g.height
+        {
+            for x in 0..g.width
+            {
+                let offsets = wall_offsets(g, x, y);
+                let nearest = |metric: fn(isize, isize) -> isize| offsets.iter().map(|&(dx, dy)| metric(dx, dy)).min();
+
+                let expected = nearest(|dx, dy| dx.abs() + dy.abs()).map_or(u32::MAX, |d| d as u32);
+                assert_eq!(cityblock.index([x, y]), Some(&expected), "cityblock at ({}, {})", x, y);
+
+                let expected = nearest(|dx, dy| dx.abs().max(dy.abs())).map_or(u32::MAX, |d| d as u32);
+                assert_eq!(chebyshev.index([x, y]), Some(&expected), "chebyshev at ({}, {})", x, y);
+
+                let expected = nearest(|dx, dy| dx * dx + dy * dy).map_or(f32::INFINITY, |d| (d as f32).sqrt());
+                let actual = *euclidean.index([x, y]).unwrap();
+                assert!(actual == expected || (actual - expected).abs() < 1e-4, "euclidean at ({}, {}): {} vs {}", x, y, actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn transforms_match_a_brute_force_search()
+    {
+        for seed in 0..20
+        {
+            let (w, h) = (5 + seed as isize % 7, 4 + seed as isize % 5);
+            let g = soup(w, h, w.min(h) - 1, seed);
+            check(&g);
+            check(&g.with_boundary(Boundary::Alive));
+        }
+    }
+
+    #[test]
+    fn transforms_are_infinite_without_walls()
+    {
+        let g = Grid::new(6, 3);
+        assert!(g.distance_transform_cityblock().as_slice().iter().all(|&d| d == u32::MAX));
+        assert!(g.distance_transform_chebyshev().as_slice().iter().all(|&d| d == u32::MAX));
+        assert!(g.distance_transform_euclidean().as_slice().iter().all(|&d| d == f32::INFINITY));
+        check(&g.with_boundary(Boundary::Alive));
+    }
+
+    #[test]
+    fn transforms_handle_one_wide_grids()
+    {
+        for seed in 0..4
+        {
+            let mut column = Grid::new(1, 9);
+            *column.index_mut([0, seed as isize * 2]).unwrap() = 1;
+            *column.index_mut([0, 8 - seed as isize]).unwrap() = 1;
+            for g in [column.transpose(), column]
+            {
+                check(&g);
+                check(&g.with_boundary(Boundary::Alive));
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use mint::Point2;
+
+use crate::GridDim;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridError
+{
+    /// `point` doesn't lie inside a grid of size `dim`
+    OutOfBounds { point: Point2<isize>, dim: GridDim },
+    /// A grid can't be built with a side that isn't positive
+    InvalidSize { width: isize, height: isize },
+    /// The cells handed over don't fill the grid exactly
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl std::fmt::Display for GridError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            GridError::OutOfBounds { point, dim } => write!(f, "({}, {}) is outside the {}x{} grid", point.x, point.y, dim.width, dim.height),
+            GridError::InvalidSize { width, height } => write!(f, "a {}x{} grid has no cells, both sides must be positive", width, height),
+            GridError::LengthMismatch { expected, found } => write!(f, "a grid of this size holds {} cells, but {} were given", expected, found),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
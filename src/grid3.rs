@@ -0,0 +1,378 @@
+use mint::Point3;
+
+use crate::{Automaton, Grid, IsRules, IsTopology};
+
+/// Which surrounding voxels count as neighbours in a [`Grid3`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Neighborhood3
+{
+    /// The 6 voxels sharing a face
+    Faces,
+    /// The 18 voxels sharing a face or an edge
+    Edges,
+    /// All 26 voxels sharing a face, edge or corner
+    #[default]
+    Vertices,
+}
+
+/// A `width * height * depth` block of voxels, stored as `depth` row-major layers.
+///
+/// Voxels outside the block are dead and can't be walked into.
+#[derive(Clone, Debug)]
+pub struct Grid3<T = u8>
+{
+    width: isize,
+    height: isize,
+    depth: isize,
+    pub neighborhood: Neighborhood3,
+    array: Vec<T>,
+}
+
+impl<T> Grid3<T>
+{
+    pub fn new(width: isize, height: isize, depth: isize) -> Self
+    where
+        T: Default + Clone
+    {
+        Grid3::new_filled(width, height, depth, T::default())
+    }
+
+    pub fn new_filled(width: isize, height: isize, depth: isize, value: T) -> Self
+    where
+        T: Clone
+    {
+        assert!(width > 0);
+        assert!(height > 0);
+        assert!(depth > 0);
+
+        Grid3
+        {
+            width,
+            height,
+            depth,
+            neighborhood: Neighborhood3::default(),
+            array: vec![value; (width * height * depth) as usize],
+        }
+    }
+
+    /// Stack equally sized 2D layers, the first one at `z = 0`
+    pub fn from_layers(layers: &[Grid<T>]) -> Self
+    where
+        T: Clone
+    {
+        assert!(!layers.is_empty());
+
+        let (width, height) = (layers[0].width, layers[0].height);
+        assert!(layers.iter().all(|l| l.width == width && l.height == height));
+
+        Grid3
+        {
+            width,
+            height,
+            depth: layers.len() as isize,
+            neighborhood: Neighborhood3::default(),
+            array: layers.iter().flat_map(|l| l.array.iter().cloned()).collect(),
+        }
+    }
+
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood3) -> Self
+    {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    pub fn width(&self) -> isize
+    {
+        self.width
+    }
+
+    pub fn height(&self) -> isize
+    {
+        self.height
+    }
+
+    pub fn depth(&self) -> isize
+    {
+        self.depth
+    }
+
+    /// The voxels layer by layer, each layer in row-major order
+    pub fn as_slice(&self) -> &[T]
+    {
+        &self.array
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T]
+    {
+        &mut self.array
+    }
+
+    pub fn contains(&self, p: impl Into<Point3<isize>>) -> bool
+    {
+        let p = p.into();
+        0 <= p.x && p.x < self.width &&
+        0 <= p.y && p.y < self.height &&
+        0 <= p.z && p.z < self.depth
+    }
+
+    pub fn index(&self, p: impl Into<Point3<isize>>) -> Option<&T>
+    {
+        let p = p.into();
+        if !self.contains(p) { return None }
+
+        self.array.get((p.x + p.y * self.width + p.z * self.width * self.height) as usize)
+    }
+
+    pub fn index_mut(&mut self, p: impl Into<Point3<isize>>) -> Option<&mut T>
+    {
+        let p = p.into();
+        if !self.contains(p) { return None }
+
+        self.array.get_mut((p.x + p.y * self.width + p.z * self.width * self.height) as usize)
+    }
+
+    /// Copy out the 2D layer at depth `z`
+    pub fn layer(&self, z: isize) -> Grid<T>
+    where
+        T: Clone
+    {
+        assert!(0 <= z && z < self.depth);
+
+        let area = (self.width * self.height) as usize;
+        let start = z as usize * area;
+        Grid::from_vec(self.width, self.height, self.array[start..start + area].to_vec())
+    }
+
+    /// Overwrite the layer at depth `z` with an equally sized 2D grid
+    pub fn set_layer(&mut self, z: isize, layer: &Grid<T>)
+    where
+        T: Clone
+    {
+        assert!(0 <= z && z < self.depth);
+        assert!(layer.width == self.width && layer.height == self.height);
+
+        let area = (self.width * self.height) as usize;
+        let start = z as usize * area;
+        self.array[start..start + area].clone_from_slice(&layer.array);
+    }
+
+    /// Every layer from `z = 0` upwards
+    pub fn layers(&self) -> impl Iterator<Item = Grid<T>> + '_
+    where
+        T: Clone
+    {
+        (0..self.depth).map(|z| self.layer(z))
+    }
+}
+
+impl Grid3
+{
+    /// The voxels around `p` in the given neighborhood, which may lie outside the grid
+    pub fn neighbors_of(p: impl Into<Point3<isize>>, neighborhood: Neighborhood3) -> impl Iterator<Item = Point3<isize>>
+    {
+        let p = p.into();
+        let reach = match neighborhood
+        {
+            Neighborhood3::Faces => 1,
+            Neighborhood3::Edges => 2,
+            Neighborhood3::Vertices => 3,
+        };
+
+        (-1..=1)
+        .flat_map(|dz| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (dx, dy, dz))))
+        .filter(move |&(dx, dy, dz): &(isize, isize, isize)|
+        {
+            let steps = dx.abs() + dy.abs() + dz.abs();
+            0 < steps && steps <= reach
+        })
+        .map(move |(dx, dy, dz)| Point3 { x: p.x + dx, y: p.y + dy, z: p.z + dz })
+    }
+
+    pub fn distance_cityblock(a: impl Into<Point3<isize>>, b: impl Into<Point3<isize>>) -> isize
+    {
+        let a = a.into();
+        let b = b.into();
+
+        (a.x - b.x).abs() + (a.y - b.y).abs() + (a.z - b.z).abs()
+    }
+
+    /// Count the live neighbours of `p` in the grid's neighborhood, with everything outside dead
+    pub fn sum_neighbors(&self, p: impl Into<Point3<isize>>) -> u8
+    {
+        self.sum_neighbors_by(p, |v| v > 0)
+    }
+
+    /// Count the neighbours of `p` whose value is `alive`, with everything outside dead
+    pub fn sum_neighbors_by(&self, p: impl Into<Point3<isize>>, alive: impl Fn(u8) -> bool) -> u8
+    {
+        Grid3::neighbors_of(p, self.neighborhood)
+        .filter(|&n| self.index(n).is_some_and(|&v| alive(v)))
+        .count() as u8
+    }
+
+    pub fn find_path_with_a_star(&self, start: Point3<isize>, end: Point3<isize>) -> Option<std::collections::HashSet<Point3<isize>>>
+    {
+        self.a_star(start, end, |p| *self.index(p).unwrap() == 0)
+    }
+}
+
+impl<T> IsTopology for Grid3<T>
+{
+    type Point = Point3<isize>;
+
+    fn adjacent(&self, p: Self::Point) -> impl Iterator<Item = Self::Point>
+    {
+        Grid3::neighbors_of(p, Neighborhood3::Faces).filter(|&n| self.contains(n))
+    }
+
+    fn distance(&self, a: Self::Point, b: Self::Point) -> isize
+    {
+        Grid3::distance_cityblock(a, b)
+    }
+}
+
+impl<R> Automaton<R, Grid3>
+where
+    R: IsRules
+{
+    /// Step every voxel by its 6, 18 or 26 neighbours, for rules like `B4/S5`.
+    /// Survivors keep their value, and Generations rules decay voxels the same as cells on a `Grid`.
+    pub fn step(&mut self)
+    {
+        let table = self.rules.table();
+        // `grid` is public, so it may have been swapped for one of another size since the last step
+        if (self.other_grid.width, self.other_grid.height, self.other_grid.depth) != (self.grid.width, self.grid.height, self.grid.depth)
+        {
+            self.other_grid = self.grid.clone();
+        }
+        self.other_grid.neighborhood = self.grid.neighborhood;
+
+        for z in 0..self.grid.depth
+        {
+            for y in 0..self.grid.height
+            {
+                for x in 0..self.grid.width
+                {
+                    let p = Point3 { x, y, z };
+                    let num_alive_neighbors = self.grid.sum_neighbors_by(p, |v| table.alive(v));
+                    *self.other_grid.index_mut(p).unwrap() = table.next_state(*self.grid.index(p).unwrap(), num_alive_neighbors as u32);
+                }
+            }
+        }
+
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+}
+
+impl_stepper!(<R: IsRules> Automaton<R, Grid3>);
+
+#[cfg(test)]
+mod tests
+{
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::tests::soup;
+    use crate::topology::tests::walk;
+    use crate::DynamicRules;
+
+    /// the centre of a 3x3x3 block with every other voxel alive, after one step under `rule`
+    fn centre_after_step(rule: &str) -> u8
+    {
+        let mut grid = Grid3::new_filled(3, 3, 3, 1u8);
+        *grid.index_mut([1, 1, 1]).unwrap() = 0;
+        assert_eq!(grid.sum_neighbors([1, 1, 1]), 26);
+
+        let mut at = Automaton::new(grid, rule.parse::<DynamicRules>().unwrap());
+        at.step();
+        *at.grid.index([1, 1, 1]).unwrap()
+    }
+
+    #[test]
+    fn moore_rules_reach_26_from_a_rule_string()
+    {
+        assert_eq!(centre_after_step("B26,/S"), 1);
+        assert_eq!(centre_after_step("B13,26/S4,5"), 1);
+        assert_eq!(centre_after_step("B25,/S"), 0);
+    }
+
+    #[test]
+    fn layers_round_trip()
+    {
+        let layers: Vec<Grid> = (0..4).map(|seed| soup(5, 5, 5, seed)).collect();
+        let mut grid = Grid3::from_layers(&layers);
+        assert_eq!((grid.width(), grid.height(), grid.depth()), (5, 5, 4));
+        assert_eq!(*grid.index([2, 3, 1]).unwrap(), *layers[1].index([2, 3]).unwrap());
+
+        for (layer, original) in grid.layers().zip(&layers)
+        {
+            assert_eq!(layer.as_slice(), original.as_slice());
+        }
+
+        grid.set_layer(2, &layers[0]);
+        assert_eq!(grid.layer(2).as_slice(), layers[0].as_slice());
+        assert_eq!(grid.layer(3).as_slice(), layers[3].as_slice());
+    }
+
+    #[test]
+    fn neighborhoods_count_6_18_and_26()
+    {
+        for (neighborhood, count, corner) in [(Neighborhood3::Faces, 6, 3), (Neighborhood3::Edges, 18, 6), (Neighborhood3::Vertices, 26, 7)]
+        {
+            let grid = Grid3::new_filled(3, 3, 3, 1u8).with_neighborhood(neighborhood);
+            assert_eq!(grid.sum_neighbors([1, 1, 1]), count);
+            assert_eq!(Grid3::neighbors_of([1, 1, 1], neighborhood).count(), count as usize);
+
+            // a corner only has the voxels inside the block
+            assert_eq!(grid.sum_neighbors([0, 0, 0]), corner);
+        }
+    }
+
+    #[test]
+    fn a_star_follows_a_carved_maze()
+    {
+        let mut grid = Grid3::new_filled(7, 7, 7, 1u8);
+        let open = grid.carve_maze([0, 0, 0].into(), &mut rand::rngs::StdRng::seed_from_u64(3));
+        for &p in &open
+        {
+            *grid.index_mut(p).unwrap() = 0;
+        }
+
+        let start = Point3 { x: 0, y: 0, z: 0 };
+        let steps = walk(&grid, start, |p| open.contains(&p));
+        assert_eq!(steps.len(), open.len());
+
+        // the open cell furthest along the maze from the start
+        let (&end, &far) = steps.iter().max_by_key(|&(_, &s)| s).unwrap();
+        let path = grid.find_path_with_a_star(start, end).unwrap();
+        assert_eq!(path.len() as isize, far);
+        assert!(path.iter().all(|p| open.contains(p)));
+    }
+
+    #[test]
+    fn survivors_keep_their_values_and_generations_decay()
+    {
+        let mut grid = Grid3::new(4, 4, 4).with_neighborhood(Neighborhood3::Faces);
+        for (p, v) in [([1, 1, 1], 5), ([2, 1, 1], 7)]
+        {
+            *grid.index_mut(p).unwrap() = v;
+        }
+
+        let mut at = Automaton::new(grid.clone(), "B/S1".parse::<DynamicRules>().unwrap());
+        at.step_n(2);
+        assert_eq!(at.grid.as_slice(), grid.as_slice());
+
+        let mut at = Automaton::new(grid.clone(), "B/S/C3".parse::<DynamicRules>().unwrap());
+        at.grid.as_mut_slice().iter_mut().for_each(|v| *v = (*v > 0) as u8);
+        at.step();
+        assert_eq!(*at.grid.index([1, 1, 1]).unwrap(), 2);
+        at.step();
+        assert_eq!(*at.grid.index([1, 1, 1]).unwrap(), 0);
+
+        at.grid = Grid3::new(2, 3, 5);
+        at.step();
+        assert_eq!((at.grid.width(), at.grid.height(), at.grid.depth()), (2, 3, 5));
+    }
+}
+
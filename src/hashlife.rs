@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use mint::Point2;
+
+use crate::{Grid, IsRules, RuleTable};
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// A square of `2^level` cells on a side, made of four `level - 1` quadrants
+/// in `[nw, ne, sw, se]` order. Level 0 nodes are single cells.
+#[derive(Clone, Copy, Debug)]
+struct Node
+{
+    level: u8,
+    children: [NodeId; 4],
+    population: u64,
+}
+
+/// Gosper's HashLife over an unbounded plane, for any outer-totalistic birth/survive rule.
+///
+/// Identical quadrants are stored once and the future of every quadrant is memoised,
+/// so patterns with any regularity can be advanced `2^k` generations in one call.
+/// Nodes are never freed, so the cache grows for the lifetime of the engine.
+#[derive(Clone, Debug)]
+pub struct HashLife
+{
+    nodes: Vec<Node>,
+    lookup: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    birth: u16,
+    survive: u16,
+    root: NodeId,
+    origin: Point2<isize>,
+    generation: u64,
+}
+
+impl HashLife
+{
+    /// An empty plane stepped by `rules`, which may not give birth on zero neighbours or have decay states
+    pub fn new(rules: &impl IsRules) -> Self
+    {
+        let table = RuleTable::two_state(rules);
+        let birth = table.birth.moore_bits();
+        let survive = table.survive.moore_bits();
+        assert!(birth & 1 == 0, "B0 rules can't be stepped on an infinite plane");
+
+        let leaf = |population| Node { level: 0, children: [DEAD; 4], population };
+
+        let mut life = HashLife
+        {
+            nodes: vec![leaf(0), leaf(1)],
+            lookup: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            birth,
+            survive,
+            root: DEAD,
+            origin: [0, 0].into(),
+            generation: 0,
+        };
+        life.root = life.empty(1);
+
+        life
+    }
+
+    /// Load `grid` with its top-left cell at the origin. Non-zero cells are alive,
+    /// and the grid's boundary is ignored since the plane has no edges.
+    pub fn from_grid(grid: &Grid, rules: &impl IsRules) -> Self
+    {
+        let mut life = HashLife::new(rules);
+
+        let side = isize::max(grid.width, grid.height);
+        let mut level = 1;
+        while (1 << level) < side
+        {
+            level += 1;
+        }
+
+        life.root = life.build(grid, level, [0, 0].into());
+        life
+    }
+
+    fn build(&mut self, grid: &Grid, level: u8, at: Point2<isize>) -> NodeId
+    {
+        if at.x >= grid.width || at.y >= grid.height
+        {
+            return self.empty(level);
+        }
+
+        if level == 0
+        {
+            return if *grid.index(at).unwrap() > 0 { ALIVE } else { DEAD };
+        }
+
+        let half = 1 << (level - 1);
+        let children = [[0, 0], [half, 0], [0, half], [half, half]]
+            .map(|[dx, dy]| self.build(grid, level - 1, [at.x + dx, at.y + dy].into()));
+
+        self.join(children)
+    }
+
+    /// Number of generations stepped so far
+    pub fn generation(&self) -> u64
+    {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64
+    {
+        self.nodes[self.root as usize].population
+    }
+
+    /// Every live cell, within the box around them and that box's top-left corner, or `None` if empty
+    pub fn to_grid(&self) -> Option<(Point2<isize>, Grid)>
+    {
+        let mut cells = Vec::new();
+        self.collect(self.root, self.origin, &mut cells);
+
+        let first = *cells.first()?;
+        let (min, max) = cells.iter().fold((first, first), |(min, max), p|
+        {
+            (
+                [isize::min(min.x, p.x), isize::min(min.y, p.y)].into(),
+                [isize::max(max.x, p.x), isize::max(max.y, p.y)].into(),
+            )
+        });
+
+        let mut grid = Grid::new(max.x - min.x + 1, max.y - min.y + 1);
+        for p in cells
+        {
+            *grid.index_mut([p.x - min.x, p.y - min.y]).unwrap() = 1;
+        }
+
+        Some((min, grid))
+    }
+
+    fn collect(&self, id: NodeId, at: Point2<isize>, cells: &mut Vec<Point2<isize>>)
+    {
+        let node = self.nodes[id as usize];
+        if node.population == 0
+        {
+            return;
+        }
+
+        if node.level == 0
+        {
+            cells.push(at);
+            return;
+        }
+
+        let half = 1 << (node.level - 1);
+        for (child, [dx, dy]) in node.children.into_iter().zip([[0, 0], [half, 0], [0, half], [half, half]])
+        {
+            self.collect(child, [at.x + dx, at.y + dy].into(), cells);
+        }
+    }
+
+    /// Advance the plane by `2^k` generations in a single pass
+    pub fn step_pow2(&mut self, k: u8)
+    {
+        assert!(k < 56, "can't jump further than 2^55 generations at once");
+
+        // the result only covers the centre half of the root, so pad until the
+        // pattern can't spread out of it in 2^k generations
+        loop
+        {
+            let padded = self.level(self.root) >= k + 2 &&
+            {
+                let center = self.center(self.root);
+                self.population_of(center) == self.population()
+            };
+
+            if padded { break }
+            self.expand();
+        }
+        self.expand();
+
+        let quarter = 1 << (self.level(self.root) - 2);
+        self.root = self.successor(self.root, k);
+        self.origin = [self.origin.x + quarter, self.origin.y + quarter].into();
+        self.generation += 1 << k;
+    }
+
+    /// Advance the plane by `n` generations, one power of two at a time
+    pub fn step(&mut self, n: u64)
+    {
+        for k in 0..64
+        {
+            if n >> k & 1 == 1
+            {
+                self.step_pow2(k);
+            }
+        }
+    }
+
+    fn level(&self, id: NodeId) -> u8
+    {
+        self.nodes[id as usize].level
+    }
+
+    fn population_of(&self, id: NodeId) -> u64
+    {
+        self.nodes[id as usize].population
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4]
+    {
+        self.nodes[id as usize].children
+    }
+
+    /// The canonical node with these quadrants
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId
+    {
+        if let Some(&id) = self.lookup.get(&children)
+        {
+            return id;
+        }
+
+        let node = Node
+        {
+            level: self.level(children[0]) + 1,
+            children,
+            population: children.iter().map(|&c| self.population_of(c)).sum(),
+        };
+
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(node);
+        self.lookup.insert(children, id);
+
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId
+    {
+        while self.empty.len() <= level as usize
+        {
+            let e = *self.empty.last().unwrap();
+            let next = self.join([e; 4]);
+            self.empty.push(next);
+        }
+
+        self.empty[level as usize]
+    }
+
+    /// The centred node one level down
+    fn center(&mut self, id: NodeId) -> NodeId
+    {
+        let [nw, ne, sw, se] = self.children(id);
+        self.join([self.children(nw)[3], self.children(ne)[2], self.children(sw)[1], self.children(se)[0]])
+    }
+
+    /// Surround the root with empty space, doubling its side
+    fn expand(&mut self)
+    {
+        let level = self.level(self.root);
+        let e = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+
+        let children = [[e, e, e, nw], [e, e, ne, e], [e, sw, e, e], [se, e, e, e]]
+            .map(|c| self.join(c));
+        self.root = self.join(children);
+
+        let half = 1 << (level - 1);
+        self.origin = [self.origin.x - half, self.origin.y - half].into();
+    }
+
+    /// The cell at `x, y` inside node `id`
+    fn cell(&self, id: NodeId, x: isize, y: isize) -> bool
+    {
+        let node = self.nodes[id as usize];
+        if node.level == 0
+        {
+            return node.population == 1;
+        }
+
+        let half = 1 << (node.level - 1);
+        let quadrant = (y >= half) as usize * 2 + (x >= half) as usize;
+        self.cell(node.children[quadrant], x % half, y % half)
+    }
+
+    /// One generation of the centre 2x2 of a 4x4 node
+    fn base(&mut self, id: NodeId) -> NodeId
+    {
+        let next = [[1, 1], [2, 1], [1, 2], [2, 2]].map(|[x, y]|
+        {
+            let count =
+                (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                .filter(|&(dx, dy)| (dx, dy) != (0, 0) && self.cell(id, x + dx, y + dy))
+                .count();
+
+            let mask = if self.cell(id, x, y) { self.survive } else { self.birth };
+            if mask >> count & 1 == 1 { ALIVE } else { DEAD }
+        });
+
+        self.join(next)
+    }
+
+    /// The centre half of node `id` advanced `2^j` generations, where `j <= level - 2`
+    fn successor(&mut self, id: NodeId, j: u8) -> NodeId
+    {
+        let node = self.nodes[id as usize];
+        if node.population == 0
+        {
+            return self.empty(node.level - 1);
+        }
+
+        if let Some(&result) = self.results.get(&(id, j))
+        {
+            return result;
+        }
+
+        let result = if node.level == 2
+        {
+            self.base(id)
+        }
+        else
+        {
+            let [a, b, c, d] = node.children;
+            let [_, a_ne, a_sw, a_se] = self.children(a);
+            let [b_nw, _, b_sw, b_se] = self.children(b);
+            let [c_nw, c_ne, _, c_se] = self.children(c);
+            let [d_nw, d_ne, d_sw, _] = self.children(d);
+
+            // nine overlapping nodes one level down, covering the node in a 3x3
+            let n01 = self.join([a_ne, b_nw, a_se, b_sw]);
+            let n10 = self.join([a_sw, a_se, c_nw, c_ne]);
+            let n11 = self.join([a_se, b_sw, c_ne, d_nw]);
+            let n12 = self.join([b_sw, b_se, d_nw, d_ne]);
+            let n21 = self.join([c_ne, d_nw, c_se, d_sw]);
+            let nine = [a, n01, b, n10, n11, n12, c, n21, d];
+
+            // at full speed both halves of the jump advance, otherwise only the second does
+            let full = j == node.level - 2;
+            let r = nine.map(|n| if full { self.successor(n, node.level - 3) } else { self.center(n) });
+
+            let second = if full { node.level - 3 } else { j };
+            let quads = [[0, 1, 3, 4], [1, 2, 4, 5], [3, 4, 6, 7], [4, 5, 7, 8]]
+                .map(|q| self.join(q.map(|i| r[i])));
+            let children = quads.map(|q| self.successor(q, second));
+
+            self.join(children)
+        };
+
+        self.results.insert((id, j), result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{tests::soup, Automaton, ConstRules};
+
+    fn live_cells(origin: Point2<isize>, grid: &Grid) -> HashSet<(isize, isize)>
+    {
+        grid.points().filter(|&p| *grid.index(p).unwrap() > 0).map(|p| (origin.x + p.x, origin.y + p.y)).collect()
+    }
+
+    #[test]
+    fn jumps_match_stepping_a_grid()
+    {
+        let rules = ConstRules::new([3, 6], [2, 3]);
+        for seed in 0..3
+        {
+            let grid = soup(64, 64, 16, seed);
+            let mut finite = Automaton::new(grid.clone(), rules.clone());
+            let mut life = HashLife::from_grid(&grid, &rules);
+
+            for n in [1, 2, 5, 13]
+            {
+                finite.step_n(n);
+                life.step(n);
+
+                let (origin, cells) = life.to_grid().unwrap();
+                assert_eq!(live_cells(origin, &cells), live_cells([0, 0].into(), &finite.grid));
+                assert_eq!(life.generation(), finite.generation());
+            }
+        }
+    }
+
+    #[test]
+    fn gliders_travel_a_quarter_cell_a_generation()
+    {
+        let glider: Grid = ".O.\n..O\nOOO".parse().unwrap();
+        let mut life = HashLife::from_grid(&glider, &ConstRules::new([3], [2, 3]));
+        life.step(1 << 20);
+
+        let (origin, cells) = life.to_grid().unwrap();
+        assert_eq!(life.population(), 5);
+        assert_eq!(origin, [1 << 18, 1 << 18].into());
+        assert_eq!(cells.as_slice(), glider.as_slice());
+    }
+}
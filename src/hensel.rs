@@ -0,0 +1,313 @@
+use mint::Point2;
+
+use crate::{table::advance, Automaton, Grid, RuleMask};
+
+/// Hensel's letters for each neighbour count up to 4, each with one configuration of its class.
+/// Bit n of a configuration is the nth neighbour in `Grid::neighbors_of` order: NW, N, NE, W, E, SW, S, SE.
+/// Counts 5 to 7 reuse the letters of `8 - count` with every neighbour flipped.
+const LETTERS: [&[(char, u8)]; 5] = [
+    &[],
+    &[('c', 1), ('e', 2)],
+    &[('c', 5), ('e', 10), ('a', 3), ('i', 24), ('k', 17), ('n', 36)],
+    &[('c', 37), ('e', 26), ('a', 11), ('i', 7), ('k', 50), ('n', 13), ('j', 14), ('q', 38), ('r', 25), ('y', 49)],
+    &[
+        ('c', 165), ('e', 90), ('a', 15), ('i', 29), ('k', 51), ('n', 39), ('j', 58),
+        ('q', 54), ('r', 27), ('t', 57), ('w', 46), ('y', 53), ('z', 60),
+    ],
+];
+
+/// where each bit of a configuration sits relative to the cell
+const OFFSETS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// The configuration rotated and reflected every way, which all share its letter
+fn symmetries(config: u8) -> impl Iterator<Item = u8>
+{
+    (0..8).map(move |t|
+    {
+        (0..8).filter(|&bit| config >> bit & 1 == 1).fold(0, |acc, bit|
+        {
+            let (mut x, mut y) = OFFSETS[bit];
+            for _ in 0..t % 4
+            {
+                (x, y) = (-y, x);
+            }
+            if t >= 4
+            {
+                x = -x;
+            }
+            acc | 1 << OFFSETS.iter().position(|&o| o == (x, y)).unwrap()
+        })
+    })
+}
+
+/// Every configuration with `count` neighbours that `letter` stands for
+fn letter_configs(count: u32, letter: char) -> Option<Vec<u8>>
+{
+    let flip = count > 4;
+    let &(_, config) = LETTERS[if flip { 8 - count } else { count } as usize].iter().find(|(l, _)| *l == letter)?;
+
+    Some(symmetries(if flip { !config } else { config }).collect())
+}
+
+/// The letters allowed after `count`, alphabetically
+fn letters_of(count: u32) -> Vec<char>
+{
+    let mut letters: Vec<char> = LETTERS[4 - (count as i32 - 4).unsigned_abs() as usize].iter().map(|&(l, _)| l).collect();
+    letters.sort();
+    letters
+}
+
+/// Isotropic non-totalistic rules, where birth and survival depend on which neighbours are alive
+/// rather than just how many, so corners and edges can be told apart.
+///
+/// Read from and written as Hensel notation like `B2-a/S12`: each count may be followed by letters
+/// picking out some of its neighbour shapes, or by `-` and the letters to leave out.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HenselRules
+{
+    /// bit n is set when a cell with neighbour configuration n is born
+    birth:      RuleMask,
+    survive:    RuleMask,
+}
+
+impl HenselRules
+{
+    /// Whether a cell is alive next generation, given whether it is now and its neighbours packed
+    /// the way `Grid::neighbor_config` packs them
+    pub fn next(&self, alive: bool, config: u8) -> bool
+    {
+        match alive
+        {
+            false => self.birth.contains(config as u32),
+            true => self.survive.contains(config as u32),
+        }
+    }
+}
+
+impl std::str::FromStr for HenselRules
+{
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let (mut birth, mut survive) = (None, None);
+        let mut section: Option<&mut Vec<u8>> = None;
+        // the count being read, whether its letters are left out, and its letters so far
+        let mut count: Option<(u32, bool, Vec<char>)> = None;
+
+        fn flush(section: &mut Option<&mut Vec<u8>>, count: Option<(u32, bool, Vec<char>)>) -> Result<(), String>
+        {
+            let (Some(section), Some((n, negated, letters))) = (section, count)
+            else
+            {
+                return Ok(());
+            };
+
+            let all = (0..=255u8).filter(|c| c.count_ones() == n);
+            if letters.is_empty()
+            {
+                section.extend(all);
+                return Ok(());
+            }
+
+            let mut picked = Vec::new();
+            for l in letters
+            {
+                picked.extend(letter_configs(n, l).ok_or(format!("{:?} is not a letter for {} neighbours", l, n))?);
+            }
+            match negated
+            {
+                false => section.extend(picked),
+                true => section.extend(all.filter(|c| !picked.contains(c))),
+            }
+            Ok(())
+        }
+
+        for c in s.trim().chars()
+        {
+            match c
+            {
+                'B' | 'b' | 'S' | 's' | '/' =>
+                {
+                    flush(&mut section, count.take())?;
+                    match c
+                    {
+                        'B' | 'b' => section = Some(birth.insert(Vec::new())),
+                        'S' | 's' => section = Some(survive.insert(Vec::new())),
+                        _ => (),
+                    }
+                },
+                '0'..='8' if section.is_some() =>
+                {
+                    flush(&mut section, count.take())?;
+                    count = Some((c as u32 - '0' as u32, false, Vec::new()));
+                },
+                '-' => match count.as_mut()
+                {
+                    Some((_, negated @ false, letters)) if letters.is_empty() => *negated = true,
+                    _ => return Err(String::from("'-' must come straight after a neighbour count")),
+                },
+                'a'..='z' => match count.as_mut()
+                {
+                    Some((_, _, letters)) => letters.push(c),
+                    None => return Err(format!("{:?} comes before a neighbour count", c)),
+                },
+                c => return Err(format!("unexpected {:?} in rule string", c)),
+            }
+        }
+        flush(&mut section, count)?;
+
+        match (birth, survive)
+        {
+            (Some(birth), Some(survive)) => Ok(HenselRules
+            {
+                birth: RuleMask::from_counts(birth.iter()),
+                survive: RuleMask::from_counts(survive.iter()),
+            }),
+            _ => Err(String::from("B or S not present in rule string")),
+        }
+    }
+}
+
+/// Writes the rule in Hensel notation, listing the letters or the left out letters, whichever is shorter
+impl std::fmt::Display for HenselRules
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let write_mask = |f: &mut std::fmt::Formatter<'_>, mask: &RuleMask| -> std::fmt::Result
+        {
+            for n in 0..=8
+            {
+                let (present, absent): (Vec<char>, Vec<char>) = letters_of(n)
+                    .into_iter()
+                    .partition(|&l| letter_configs(n, l).unwrap().iter().all(|&c| mask.contains(c as u32)));
+
+                match (n, present.len(), absent.len())
+                {
+                    (0 | 8, _, _) if mask.contains(if n == 0 { 0 } else { 255 }) => write!(f, "{}", n)?,
+                    (_, 0, _) => (),
+                    (_, _, 0) => write!(f, "{}", n)?,
+                    (_, p, a) if p <= a => write!(f, "{}{}", n, present.iter().collect::<String>())?,
+                    _ => write!(f, "{}-{}", n, absent.iter().collect::<String>())?,
+                }
+            }
+            Ok(())
+        };
+
+        write!(f, "B")?;
+        write_mask(f, &self.birth)?;
+        write!(f, "/S")?;
+        write_mask(f, &self.survive)
+    }
+}
+
+impl Grid
+{
+    /// The 8 neighbours of `p` packed into a byte, bit n set when the nth neighbour of `neighbors_of` is alive,
+    /// with cells past the edges decided by the boundary
+    pub fn neighbor_config(&self, p: impl Into<Point2<isize>>) -> u8
+    {
+        Self::neighbors_of(p)
+        .enumerate()
+        .fold(0, |acc, (bit, n)| acc | (self.alive_through_boundary(n) as u8) << bit)
+    }
+}
+
+impl Automaton<HenselRules>
+{
+    /// Step every cell by which of its 8 neighbours are alive, looking the neighbour configuration up in the rule's masks,
+    /// so cells with the same count can still step differently
+    pub fn step(&mut self)
+    {
+        self.prepare_other_grid();
+
+        for p in self.grid.points()
+        {
+            let v = *self.grid.index(p).unwrap();
+            let alive = self.rules.next(v > 0, self.grid.neighbor_config(p));
+            *self.other_grid.index_mut(p).unwrap() = advance(2, v, alive);
+        }
+
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+}
+
+impl_stepper!(Automaton<HenselRules>);
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{tests::soup, Boundary, ConstRules};
+
+    #[test]
+    fn letters_split_each_count_into_disjoint_classes()
+    {
+        for n in 1..=7
+        {
+            let mut configs = Vec::new();
+            for l in letters_of(n)
+            {
+                let mut class = letter_configs(n, l).unwrap();
+                class.sort();
+                class.dedup();
+                assert!(class.iter().all(|c| !configs.contains(c)), "{}{} overlaps another letter", n, l);
+                configs.extend(class);
+            }
+
+            configs.sort();
+            assert_eq!(configs, (0..=255u8).filter(|c| c.count_ones() == n).collect::<Vec<u8>>(), "{} neighbours", n);
+        }
+    }
+
+    #[test]
+    fn totalistic_rules_step_like_life()
+    {
+        let grid = soup(40, 40, 40, 7);
+        for boundary in [Boundary::Dead, Boundary::Alive, Boundary::Wrap, Boundary::Mirror, Boundary::Klein]
+        {
+            let mut life = Automaton::new(grid.clone().with_boundary(boundary), ConstRules::new([3], [2, 3]));
+            let mut hensel = Automaton::new(grid.clone().with_boundary(boundary), "B3/S23".parse::<HenselRules>().unwrap());
+
+            for _ in 0..8
+            {
+                life.step();
+                hensel.step();
+                assert_eq!(hensel.grid.as_slice(), life.grid.as_slice(), "{:?}", boundary);
+            }
+        }
+    }
+
+    #[test]
+    fn letters_tell_shapes_apart()
+    {
+        let rules: HenselRules = "B2a/S".parse().unwrap();
+
+        // two cells side by side in a corner, and two cells opposite each other
+        assert!(rules.next(false, 0b0000_0011));
+        assert!(!rules.next(false, 0b0100_0010));
+        assert!(!rules.next(true, 0b0000_0011));
+    }
+
+    #[test]
+    fn rule_strings_round_trip()
+    {
+        for s in ["B3/S23", "B2-a/S12", "B2ce3-ak/S0", "B/S8", "B35y/S1c4-z7e"]
+        {
+            let rules: HenselRules = s.parse().unwrap();
+            assert_eq!(rules.to_string().parse::<HenselRules>().unwrap(), rules, "{}", s);
+        }
+
+        assert_eq!("b2-a/s12".parse::<HenselRules>().unwrap().to_string(), "B2-a/S12");
+        assert_eq!("B3aceijknqry/S23".parse::<HenselRules>().unwrap().to_string(), "B3/S23");
+    }
+
+    #[test]
+    fn bad_rule_strings_are_rejected()
+    {
+        for s in ["B3/", "3/23", "B3x/S23", "B-3/S", "B3/S2--a", "B3a-c/S", "B9/S", "B3/S23?", "Ba/S"]
+        {
+            assert!(s.parse::<HenselRules>().is_err(), "{:?}", s);
+        }
+    }
+}
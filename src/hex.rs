@@ -0,0 +1,345 @@
+use mint::Point2;
+use rand::Rng;
+
+use crate::{Automaton, Boundary, Grid, IsRules, IsTopology};
+
+/// Hexagonal cells kept in a `Grid` with axial coordinates, the same layout Golly uses.
+///
+/// Each cell touches the six cells at `(±1, 0)`, `(0, ±1)`, `(-1, -1)` and `(+1, +1)`,
+/// so the stored rectangle is a rhombus when drawn as hexagons.
+#[derive(Clone, Debug)]
+pub struct HexGrid<T = u8>
+{
+    pub grid: Grid<T>,
+}
+
+impl<T> std::ops::Deref for HexGrid<T>
+{
+    type Target = Grid<T>;
+
+    fn deref(&self) -> &Self::Target
+    {
+        &self.grid
+    }
+}
+
+impl<T> std::ops::DerefMut for HexGrid<T>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        &mut self.grid
+    }
+}
+
+impl<T> std::fmt::Display for HexGrid<T>
+where
+    T: std::fmt::Display
+{
+    /// Each row is shifted half a cell left of the one above it, so neighbours line up as hexagons
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        writeln!(f, "width: {} -- height: {}", self.width, self.height)?;
+
+        for row in 0..self.height
+        {
+            write!(f, "{}", " ".repeat((self.height - 1 - row) as usize))?;
+            for col in 0..self.width
+            {
+                write!(f, "{} ", self.index([col, row]).unwrap())?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> HexGrid<T>
+{
+    pub fn new(width: isize, height: isize) -> Self
+    where
+        T: Default + Clone
+    {
+        HexGrid { grid: Grid::new(width, height) }
+    }
+
+    pub fn from_grid(grid: Grid<T>) -> Self
+    {
+        HexGrid { grid }
+    }
+}
+
+impl HexGrid
+{
+    /// The 6 surrounding points of `p`, which may lie outside the grid
+    pub fn neighbors_of(p: impl Into<Point2<isize>>) -> impl Iterator<Item = Point2<isize>>
+    {
+        let p = p.into();
+        let x = p.x;
+        let y = p.y;
+        [
+            [x-1, y-1].into(),  [x, y-1].into(),
+            [x-1, y].into(),                        [x+1, y].into(),
+                                [x, y+1].into(),    [x+1, y+1].into(),
+        ].into_iter()
+    }
+
+    pub fn distance_hex(a: impl Into<Point2<isize>>, b: impl Into<Point2<isize>>) -> isize
+    {
+        let a = a.into();
+        let b = b.into();
+        let dx = a.x - b.x;
+        let dy = a.y - b.y;
+
+        isize::max(isize::max(dx.abs(), dy.abs()), (dx - dy).abs())
+    }
+
+    /// Convert axial coordinates to offset ones, where each row is a straight line
+    /// and odd rows sit half a cell to the right
+    pub fn axial_to_offset(p: impl Into<Point2<isize>>) -> Point2<isize>
+    {
+        let p = p.into();
+        [p.x - (p.y + (p.y & 1)) / 2, p.y].into()
+    }
+
+    pub fn offset_to_axial(p: impl Into<Point2<isize>>) -> Point2<isize>
+    {
+        let p = p.into();
+        [p.x + (p.y + (p.y & 1)) / 2, p.y].into()
+    }
+
+    /// Count the live hex neighbours of `p`, with cells past the edges decided by the boundary
+    pub fn sum_neighbors(&self, p: impl Into<Point2<isize>>) -> u8
+    {
+        self.sum_neighbors_by(p, |v| v > 0)
+    }
+
+    /// Count the hex neighbours of `p` whose value is `alive`, with cells past the edges decided by the boundary
+    pub fn sum_neighbors_by(&self, p: impl Into<Point2<isize>>, alive: impl Fn(u8) -> bool) -> u8
+    {
+        HexGrid::neighbors_of(p)
+        .fold(0, |acc, p|
+        {
+            acc + match self.dim.resolve(p)
+            {
+                Some(p) => alive(*self.index(p).unwrap()) as u8,
+                None => (self.boundary == Boundary::Alive) as u8,
+            }
+        })
+    }
+
+    pub fn find_path_with_a_star(&self, start: Point2<isize>, end: Point2<isize>) -> Option<std::collections::HashSet<Point2<isize>>>
+    {
+        self.a_star(start, end, |p| *self.index(p).unwrap() == 0)
+    }
+
+    /// A perfect hex maze of open (0) corridors carved out of solid wall (1)
+    pub fn generate_maze(width: isize, height: isize, rng: &mut impl Rng) -> Self
+    {
+        let mut hex = HexGrid::from_grid(Grid::new_filled(width, height, 1));
+        for p in hex.carve_maze([0, 0].into(), rng)
+        {
+            *hex.index_mut(p).unwrap() = 0;
+        }
+
+        hex
+    }
+}
+
+impl<T> IsTopology for HexGrid<T>
+{
+    type Point = Point2<isize>;
+
+    fn adjacent(&self, p: Self::Point) -> impl Iterator<Item = Self::Point>
+    {
+        HexGrid::neighbors_of(p).filter_map(|n| self.dim.resolve(n))
+    }
+
+    fn distance(&self, a: Self::Point, b: Self::Point) -> isize
+    {
+        match self.boundary
+        {
+            Boundary::Dead | Boundary::Alive | Boundary::Mirror => HexGrid::distance_hex(a, b),
+            // the shortest way round may cross any edge
+            Boundary::Wrap =>
+            {
+                (-1..=1)
+                .flat_map(|i| (-1..=1).map(move |j| (i, j)))
+                .map(|(i, j)| HexGrid::distance_hex(a, [b.x + i * self.width, b.y + j * self.height]))
+                .min()
+                .unwrap()
+            },
+            // the copies of `b` above and below the grid have their columns flipped, which turns
+            // the hexes the other way, so fall back to the larger of the row and column gaps:
+            // no step changes either by more than one, whichever way the hexes face
+            Boundary::Klein =>
+            {
+                (-1..=1)
+                .flat_map(|i| (-1..=1).map(move |j| (i, j)))
+                .map(|(i, j)|
+                {
+                    let x = if j == 0 { b.x } else { self.width - 1 - b.x };
+                    isize::max((a.x - x - i * self.width).abs(), (a.y - b.y - j * self.height).abs())
+                })
+                .min()
+                .unwrap()
+            },
+        }
+    }
+}
+
+impl<R> Automaton<R, HexGrid>
+where
+    R: IsRules
+{
+    pub fn set_boundary(&mut self, boundary: Boundary)
+    {
+        self.grid.dim.boundary = boundary;
+        self.other_grid.dim.boundary = boundary;
+    }
+
+    /// Step every cell by its 6 hex neighbours, for rules like Golly's `B2/S34H`.
+    /// Survivors keep their value, and Generations rules decay cells the same as on a `Grid`.
+    pub fn step(&mut self)
+    {
+        let table = self.rules.table();
+        // `grid` is public, so it may have been swapped for one of another size since the last step
+        if self.other_grid.array.len() != self.grid.array.len()
+        {
+            self.other_grid = self.grid.clone();
+        }
+        self.other_grid.dim = self.grid.dim;
+
+        for p in self.grid.points()
+        {
+            let num_alive_neighbors = self.grid.sum_neighbors_by(p, |v| table.alive(v));
+            *self.other_grid.index_mut(p).unwrap() = table.next_state(*self.grid.index(p).unwrap(), num_alive_neighbors as u32);
+        }
+
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+}
+
+impl_stepper!(<R: IsRules> Automaton<R, HexGrid>);
+
+#[cfg(test)]
+mod tests
+{
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::topology::tests::{assert_perfect_maze, walk};
+    use crate::DynamicRules;
+
+    #[test]
+    fn distance_is_the_shortest_walk_through_the_boundary()
+    {
+        for boundary in [Boundary::Dead, Boundary::Wrap]
+        {
+            let hex = HexGrid::<u8>::from_grid(Grid::new(7, 6).with_boundary(boundary));
+            for a in hex.points()
+            {
+                for (b, steps) in walk(&hex, a, |_| true)
+                {
+                    assert_eq!(hex.distance(a, b), steps, "{:?} from {:?} to {:?}", boundary, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn klein_distance_never_overestimates()
+    {
+        let hex = HexGrid::<u8>::from_grid(Grid::new(7, 6).with_boundary(Boundary::Klein));
+        for a in hex.points()
+        {
+            for (b, steps) in walk(&hex, a, |_| true)
+            {
+                let distance = hex.distance(a, b);
+                assert!(distance <= steps, "from {:?} to {:?}", a, b);
+                assert_eq!(distance == 0, a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn survivors_keep_their_values()
+    {
+        let mut hex = HexGrid::from_grid(Grid::new(6, 6));
+        for (p, v) in [([2, 2], 5), ([3, 2], 7), ([3, 3], 9)]
+        {
+            *hex.index_mut(p).unwrap() = v;
+        }
+
+        // each cell of the triangle touches the other two
+        let mut at = Automaton::new(hex.clone(), "B/S2".parse::<DynamicRules>().unwrap());
+        at.step_n(3);
+        assert_eq!(at.grid.as_slice(), hex.as_slice());
+    }
+
+    #[test]
+    fn generations_rules_decay_cells()
+    {
+        let mut hex = HexGrid::from_grid(Grid::new(6, 6));
+        *hex.index_mut([2, 2]).unwrap() = 1;
+        *hex.index_mut([3, 2]).unwrap() = 1;
+
+        // Brian's Brain on hexes: the pair dies into state 2 and the two cells touching both are born
+        let mut at = Automaton::new(hex, "B2/S/C3".parse::<DynamicRules>().unwrap());
+        at.step();
+        assert_eq!(*at.grid.index([2, 2]).unwrap(), 2);
+        assert_eq!(*at.grid.index([3, 3]).unwrap(), 1);
+        assert_eq!(*at.grid.index([2, 1]).unwrap(), 1);
+
+        // a decaying cell doesn't count as a neighbour
+        at.step();
+        assert_eq!(*at.grid.index([2, 2]).unwrap(), 0);
+
+        // the spare buffer follows the grid when it's swapped for one of another size
+        at.grid = HexGrid::from_grid(Grid::new(3, 9));
+        at.step();
+        assert_eq!((at.grid.width, at.grid.height), (3, 9));
+    }
+
+    #[test]
+    fn offset_coordinates_round_trip()
+    {
+        for y in -5..5
+        {
+            for x in -5..5
+            {
+                let p = Point2 { x, y };
+                assert_eq!(HexGrid::offset_to_axial(HexGrid::axial_to_offset(p)), p);
+                assert_eq!(HexGrid::axial_to_offset(HexGrid::offset_to_axial(p)), p);
+            }
+        }
+
+        // odd rows sit half a cell to the right, so the cell below-right keeps its offset column
+        assert_eq!(HexGrid::axial_to_offset([1, 1]), [0, 1].into());
+    }
+
+    #[test]
+    fn every_cell_has_six_mutual_neighbours_on_a_torus()
+    {
+        let hex = HexGrid::<u8>::from_grid(Grid::new(6, 5).with_boundary(Boundary::Wrap));
+        for a in hex.points()
+        {
+            let around: std::collections::HashSet<Point2<isize>> = hex.adjacent(a).collect();
+            assert_eq!(around.len(), 6, "{:?}", a);
+            assert!(around.iter().all(|&b| hex.adjacent(b).any(|c| c == a)), "{:?}", a);
+        }
+    }
+
+    #[test]
+    fn mazes_are_spanning_trees_of_open_cells()
+    {
+        for seed in 0..4
+        {
+            let maze = HexGrid::generate_maze(11, 9, &mut rand::rngs::StdRng::seed_from_u64(seed));
+            let open = maze.points().filter(|&p| *maze.index(p).unwrap() == 0).collect();
+            assert_perfect_maze(&maze, &open);
+        }
+    }
+}
+
@@ -0,0 +1,320 @@
+use std::collections::{HashMap, HashSet};
+
+use mint::Point2;
+
+use crate::{Automaton, Grid, IsRules, RuleTable};
+
+/// Width and height of a single chunk of an [`InfiniteGrid`]
+pub const CHUNK_SIZE: isize = 64;
+
+const CHUNK_AREA: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+#[derive(Clone, Debug)]
+struct Chunk
+{
+    cells: Box<[u8; CHUNK_AREA]>,
+    alive: usize,
+}
+
+impl Chunk
+{
+    fn new() -> Self
+    {
+        Chunk
+        {
+            cells: Box::new([0; CHUNK_AREA]),
+            alive: 0,
+        }
+    }
+}
+
+/// An unbounded plane of cells, stored as `CHUNK_SIZE` square tiles that are only
+/// allocated while they hold a live (non-zero) cell.
+#[derive(Clone, Debug, Default)]
+pub struct InfiniteGrid
+{
+    chunks: HashMap<Point2<isize>, Chunk>,
+}
+
+/// chunk coordinate of `p` and the index of `p` within that chunk
+fn split(p: Point2<isize>) -> (Point2<isize>, usize)
+{
+    let chunk = [p.x.div_euclid(CHUNK_SIZE), p.y.div_euclid(CHUNK_SIZE)].into();
+    let local = p.x.rem_euclid(CHUNK_SIZE) + p.y.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE;
+
+    (chunk, local as usize)
+}
+
+impl InfiniteGrid
+{
+    pub fn new() -> Self
+    {
+        InfiniteGrid::default()
+    }
+
+    pub fn get(&self, p: impl Into<Point2<isize>>) -> u8
+    {
+        let (chunk, local) = split(p.into());
+        self.chunks.get(&chunk).map_or(0, |c| c.cells[local])
+    }
+
+    /// Set the cell at `p`, allocating its chunk on demand and freeing it once it is empty
+    pub fn set(&mut self, p: impl Into<Point2<isize>>, value: u8)
+    {
+        let (key, local) = split(p.into());
+
+        if value == 0 && !self.chunks.contains_key(&key)
+        {
+            return;
+        }
+
+        let chunk = self.chunks.entry(key).or_insert_with(Chunk::new);
+        let cell = &mut chunk.cells[local];
+
+        match (*cell > 0, value > 0)
+        {
+            (false, true) => chunk.alive += 1,
+            (true, false) => chunk.alive -= 1,
+            _ => (),
+        }
+        *cell = value;
+
+        if chunk.alive == 0
+        {
+            self.chunks.remove(&key);
+        }
+    }
+
+    /// Number of live cells
+    pub fn population(&self) -> usize
+    {
+        self.chunks.values().map(|c| c.alive).sum()
+    }
+
+    /// Number of chunks currently allocated
+    pub fn chunk_count(&self) -> usize
+    {
+        self.chunks.len()
+    }
+
+    /// Iterate over the position and value of every live cell, in no particular order
+    pub fn live_cells(&self) -> impl Iterator<Item = (Point2<isize>, u8)> + '_
+    {
+        self.chunks
+        .iter()
+        .flat_map(|(key, chunk)|
+        {
+            chunk.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v > 0)
+            .map(move |(i, &v)|
+            {
+                let i = i as isize;
+                let p = Point2
+                {
+                    x: key.x * CHUNK_SIZE + i % CHUNK_SIZE,
+                    y: key.y * CHUNK_SIZE + i / CHUNK_SIZE,
+                };
+                (p, v)
+            })
+        })
+    }
+
+    /// The smallest and largest corner of the box around every live cell, or `None` if there are none
+    pub fn bounding_box(&self) -> Option<(Point2<isize>, Point2<isize>)>
+    {
+        self.live_cells()
+        .map(|(p, _)| p)
+        .fold(None, |acc, p|
+        {
+            let (min, max) = acc.unwrap_or((p, p));
+            Some((
+                [isize::min(min.x, p.x), isize::min(min.y, p.y)].into(),
+                [isize::max(max.x, p.x), isize::max(max.y, p.y)].into(),
+            ))
+        })
+    }
+
+    /// Copy `grid` onto the plane with its top-left cell at `at`
+    pub fn paste(&mut self, grid: &Grid, at: impl Into<Point2<isize>>)
+    {
+        let at = at.into();
+        for y in 0..grid.height
+        {
+            for x in 0..grid.width
+            {
+                self.set([at.x + x, at.y + y], *grid.index([x, y]).unwrap());
+            }
+        }
+    }
+
+    pub fn from_grid(grid: &Grid, at: impl Into<Point2<isize>>) -> Self
+    {
+        let mut plane = InfiniteGrid::new();
+        plane.paste(grid, at);
+        plane
+    }
+
+    /// Copy the `width * height` window with its top-left cell at `at` into a finite grid
+    pub fn window(&self, at: impl Into<Point2<isize>>, width: isize, height: isize) -> Grid
+    {
+        let at = at.into();
+        let mut grid = Grid::new(width, height);
+        for y in 0..height
+        {
+            for x in 0..width
+            {
+                *grid.index_mut([x, y]).unwrap() = self.get([at.x + x, at.y + y]);
+            }
+        }
+
+        grid
+    }
+
+    /// The window around every live cell and the position of its top-left corner, or `None` if empty
+    pub fn to_grid(&self) -> Option<(Point2<isize>, Grid)>
+    {
+        let (min, max) = self.bounding_box()?;
+        Some((min, self.window(min, max.x - min.x + 1, max.y - min.y + 1)))
+    }
+
+    /// Chunk `key` with a one cell border borrowed from its neighbours
+    fn padded_chunk(&self, key: Point2<isize>) -> Vec<u8>
+    {
+        let side = CHUNK_SIZE + 2;
+        let mut padded = vec![0; (side * side) as usize];
+
+        if let Some(chunk) = self.chunks.get(&key)
+        {
+            for y in 0..CHUNK_SIZE
+            {
+                let src = (y * CHUNK_SIZE) as usize;
+                let dst = ((y + 1) * side + 1) as usize;
+                padded[dst..dst + CHUNK_SIZE as usize].copy_from_slice(&chunk.cells[src..src + CHUNK_SIZE as usize]);
+            }
+        }
+
+        let origin = Point2 { x: key.x * CHUNK_SIZE - 1, y: key.y * CHUNK_SIZE - 1 };
+        for i in 0..side
+        {
+            for (x, y) in [(i, 0), (i, side - 1), (0, i), (side - 1, i)]
+            {
+                padded[(x + y * side) as usize] = self.get([origin.x + x, origin.y + y]);
+            }
+        }
+
+        padded
+    }
+
+    /// Write the next generation under `rules` into `out`.
+    ///
+    /// Panics if `rules` give birth on zero neighbours, which would fill the whole plane,
+    /// or have Generations decay states.
+    pub fn step_into(&self, rules: &impl IsRules, out: &mut InfiniteGrid)
+    {
+        let table = RuleTable::two_state(rules);
+        assert!(!table.birth.contains(0), "B0 rules can't be stepped on an infinite plane");
+
+        out.chunks.clear();
+
+        // births can spill one cell into any neighbouring chunk
+        let candidates: HashSet<Point2<isize>> =
+            self.chunks
+            .keys()
+            .flat_map(|&k|
+            {
+                (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| Point2 { x: k.x + dx, y: k.y + dy }))
+            })
+            .collect();
+
+        let side = CHUNK_SIZE + 2;
+        for key in candidates
+        {
+            let padded = self.padded_chunk(key);
+            let mut chunk = Chunk::new();
+
+            for y in 0..CHUNK_SIZE
+            {
+                for x in 0..CHUNK_SIZE
+                {
+                    let center = (x + 1) + (y + 1) * side;
+                    let num_alive_neighbors =
+                        [-side - 1, -side, -side + 1, -1, 1, side - 1, side, side + 1]
+                        .iter()
+                        .filter(|&&d| padded[(center + d) as usize] > 0)
+                        .count() as u8;
+
+                    let value = padded[center as usize];
+                    if table.next(value > 0, num_alive_neighbors as u32)
+                    {
+                        // survivors keep whatever value they had, like on a Grid
+                        chunk.cells[(x + y * CHUNK_SIZE) as usize] = value.max(1);
+                        chunk.alive += 1;
+                    }
+                }
+            }
+
+            if chunk.alive > 0
+            {
+                out.chunks.insert(key, chunk);
+            }
+        }
+    }
+}
+
+impl<R> Automaton<R, InfiniteGrid>
+where
+    R: IsRules
+{
+    /// Step the plane, growing into new chunks and dropping ones that die out
+    pub fn step(&mut self)
+    {
+        self.grid.step_into(&self.rules, &mut self.other_grid);
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+}
+
+impl_stepper!(<R: IsRules> Automaton<R, InfiniteGrid>);
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{tests::soup, ConstRules};
+
+    #[test]
+    fn steps_like_a_grid_with_room_to_grow()
+    {
+        for seed in 0..4
+        {
+            let grid = soup(48, 48, 16, seed);
+            let mut finite = Automaton::new(grid.clone(), ConstRules::new([3], [2, 3]));
+            let mut plane = Automaton::new(InfiniteGrid::from_grid(&grid, [-5, 70]), ConstRules::new([3], [2, 3]));
+
+            for _ in 0..12
+            {
+                finite.step();
+                plane.step();
+                assert_eq!(plane.grid.window([-5, 70], 48, 48).as_slice(), finite.grid.as_slice());
+                assert_eq!(plane.grid.population(), finite.grid.as_slice().iter().filter(|&&v| v > 0).count());
+            }
+        }
+    }
+
+    #[test]
+    fn survivors_keep_their_values()
+    {
+        // a block is a still life, so every cell survives
+        let mut grid = Grid::new(2, 2);
+        for (i, v) in [3, 7, 1, 200].into_iter().enumerate()
+        {
+            *grid.index_mut([i as isize % 2, i as isize / 2]).unwrap() = v;
+        }
+
+        let mut at = Automaton::new(InfiniteGrid::from_grid(&grid, [0, 0]), ConstRules::new([3], [2, 3]));
+        at.step_n(3);
+        assert_eq!(at.grid.to_grid().unwrap().1.as_slice(), grid.as_slice());
+    }
+}
@@ -1,29 +1,216 @@
 use mint::Point2;
 
+/// Implement `IsStepper` for an automaton by forwarding to its inherent `step`,
+/// as `impl_stepper!(Automaton<LtlRules>)` or `impl_stepper!(<R: IsRules> Automaton<R, HexGrid>)`
+macro_rules! impl_stepper
+{
+    (<$r:ident: $bound:path> $ty:ty) =>
+    {
+        impl<$r: $bound> $crate::IsStepper for $ty
+        {
+            fn step(&mut self)
+            {
+                Self::step(self)
+            }
+        }
+    };
+    ($ty:ty) =>
+    {
+        impl $crate::IsStepper for $ty
+        {
+            fn step(&mut self)
+            {
+                Self::step(self)
+            }
+        }
+    };
+}
+
+mod error;
+pub use error::GridError;
+
+mod bitgrid;
+pub use bitgrid::BitGrid;
+
+mod infinite;
+pub use infinite::{InfiniteGrid, CHUNK_SIZE};
+
+mod hashlife;
+pub use hashlife::HashLife;
+
+mod topology;
+pub use topology::IsTopology;
+
+mod hex;
+pub use hex::HexGrid;
+
+mod tri;
+pub use tri::{TriGrid, TriNeighborhood};
+
+mod grid3;
+pub use grid3::{Grid3, Neighborhood3};
+
+mod view;
+pub use view::{Anchor, BlendMode, GridView, GridViewMut};
+
+mod transform;
+
+mod regions;
+pub use regions::{Connectivity, Region, RegionLabels};
+
+mod morphology;
+pub use morphology::StructuringElement;
+
+mod distance;
+
+mod convolve;
+pub use convolve::Kernel;
+
+mod table;
+pub use table::{RuleMask, RuleTable};
+
+mod ltl;
+pub use ltl::{LtlNeighborhood, LtlRules};
+
+mod hensel;
+pub use hensel::HenselRules;
+
+mod netpbm;
+pub use netpbm::{decay_palette, NetpbmEncoding, NetpbmError, WALL_PALETTE};
+
+mod pattern;
+pub use pattern::{Pattern, PatternError, LIFE106_MAX_CELLS, RLE_MAX_CELLS};
+
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "serde")]
+pub use serialize::GRID_MAX_CELLS;
+
+/// A `width * height` array of cells stored in row-major order.
+///
+/// Defaults to `u8` cells where `0` is open and anything else is a wall,
+/// which is what the automaton and the pathfinders expect.
 #[derive(Clone, Debug)]
-pub struct Grid
+pub struct Grid<T = u8>
 {
-    pub dim: GridDim,
-    pub array:  Vec<u8>,
+    pub(crate) dim:    GridDim,
+    pub(crate) array:  Vec<T>,
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GridDim
 {
     pub width: isize,
     pub height: isize,
+    pub boundary: Boundary,
+}
+
+/// What lies past the edges of a grid
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Boundary
+{
+    /// Everything outside is dead, and can't be walked into
+    #[default]
+    Dead,
+    /// Everything outside is alive, and can't be walked into
+    Alive,
+    /// Opposite edges are joined, making a torus
+    Wrap,
+    /// The grid is reflected at each edge, so the cell just past an edge is the edge cell itself
+    Mirror,
+    /// Left and right edges are joined, top and bottom are joined with the columns reversed
+    Klein,
 }
 
 impl GridDim
 {
-    pub fn  index_map(&self, p: impl Into<Point2<isize>>) -> isize
+    /// Position of `p` in the row-major array, checked so that coordinates past
+    /// an edge can't alias into the next row
+    pub fn  index_map(&self, p: impl Into<Point2<isize>>) -> Result<usize, GridError>
     {
         let p = p.into();
-        p.x + p.y * self.width
+        if !self.contains(p)
+        {
+            return Err(GridError::OutOfBounds { point: p, dim: *self });
+        }
+
+        Ok((p.x + p.y * self.width) as usize)
+    }
+
+    /// Every point inside the grid, in row-major order
+    pub fn points(&self) -> impl Iterator<Item = Point2<isize>>
+    {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point2 { x, y }))
+    }
+
+    pub fn contains(&self, p: impl Into<Point2<isize>>) -> bool
+    {
+        let p = p.into();
+        0 <= p.x && p.x < self.width &&
+        0 <= p.y && p.y < self.height
+    }
+
+    /// Map `p` onto the cell it refers to under the boundary, 
+    /// or `None` if it falls outside a `Dead` or `Alive` edge
+    pub fn resolve(&self, p: impl Into<Point2<isize>>) -> Option<Point2<isize>>
+    {
+        let p = p.into();
+        if self.contains(p)
+        {
+            return Some(p);
+        }
+
+        fn reflect(v: isize, len: isize) -> isize
+        {
+            let m = v.rem_euclid(2 * len);
+            if m < len { m } else { 2 * len - 1 - m }
+        }
+
+        let (w, h) = (self.width, self.height);
+        match self.boundary
+        {
+            Boundary::Dead | Boundary::Alive => None,
+            Boundary::Wrap => Some([p.x.rem_euclid(w), p.y.rem_euclid(h)].into()),
+            Boundary::Mirror => Some([reflect(p.x, w), reflect(p.y, h)].into()),
+            Boundary::Klein =>
+            {
+                // every trip across the top or bottom edge flips the columns
+                let x = if p.y.div_euclid(h) % 2 == 0 { p.x } else { w - 1 - p.x };
+                Some([x.rem_euclid(w), p.y.rem_euclid(h)].into())
+            },
+        }
+    }
+
+    /// A lower bound on the number of 4-connected steps between `a` and `b`,
+    /// taking shortcuts across joined edges into account
+    pub fn cityblock_through_boundary(&self, a: impl Into<Point2<isize>>, b: impl Into<Point2<isize>>) -> isize
+    {
+        let a = a.into();
+        let b = b.into();
+
+        let (w, h) = (self.width, self.height);
+        let wrapped = |d: isize, len: isize| isize::min(d.abs(), len - d.abs());
+
+        match self.boundary
+        {
+            Boundary::Dead | Boundary::Alive | Boundary::Mirror => (a.x - b.x).abs() + (a.y - b.y).abs(),
+            Boundary::Wrap => wrapped(a.x - b.x, w) + wrapped(a.y - b.y, h),
+            Boundary::Klein =>
+            {
+                let dy = (a.y - b.y).abs();
+                isize::min(
+                    wrapped(a.x - b.x, w) + dy,
+                    wrapped(a.x - (w - 1 - b.x), w) + h - dy,
+                )
+            },
+        }
     }
 }
 
-impl std::ops::Deref for Grid
+impl<T> std::ops::Deref for Grid<T>
 {
     type Target = GridDim;
 
@@ -33,49 +220,185 @@ impl std::ops::Deref for Grid
     }
 }
 
-impl std::ops::DerefMut for Grid
-{
-    fn deref_mut(&mut self) -> &mut Self::Target 
-    {
-        &mut self.dim    
-    }
-}
 
-impl std::fmt::Display for Grid
+impl<T> std::fmt::Display for Grid<T>
+where
+    T: std::fmt::Display
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result 
     {
-        write!(f, "width: {} -- height: {}\n", self.width, self.height)?;
+        writeln!(f, "width: {} -- height: {}", self.width, self.height)?;
         
         for row in 0..self.height
         {
             for col in 0..self.width
             {
-                write!(f, "{} ", self.array[self.index_map([col, row]) as usize])?;
+                write!(f, "{} ", self.index([col, row]).unwrap())?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         
         Ok(())
     }
 }
 
-impl Grid
+impl<T> Grid<T>
 {
     pub fn new(width: isize, height: isize) -> Self
+    where
+        T: Default + Clone
+    {
+        Grid::new_filled(width, height, T::default())
+    }
+
+    pub fn new_filled(width: isize, height: isize, value: T) -> Self
+    where
+        T: Clone
     {
         assert!(width > 0);
         assert!(height > 0);
+        let area = width.checked_mul(height).expect("grid area overflows isize");
 
-        let dim = GridDim { width, height };
+        let dim = GridDim { width, height, boundary: Boundary::default() };
 
         Grid
         {
             dim,
-            array: vec![0; (width * height) as usize],
+            array: vec![value; area as usize],
         }
     }
 
+    /// Wrap an existing row-major array, which must hold exactly `width * height` cells
+    pub fn from_vec(width: isize, height: isize, array: Vec<T>) -> Self
+    {
+        Grid::try_from_vec(width, height, array).unwrap()
+    }
+
+    pub fn try_from_vec(width: isize, height: isize, array: Vec<T>) -> Result<Self, GridError>
+    {
+        let area = match width.checked_mul(height)
+        {
+            Some(area) if width > 0 && height > 0 => area as usize,
+            _ => return Err(GridError::InvalidSize { width, height }),
+        };
+        if array.len() != area
+        {
+            return Err(GridError::LengthMismatch { expected: area, found: array.len() });
+        }
+
+        let dim = GridDim { width, height, boundary: Boundary::default() };
+
+        Ok(Grid
+        {
+            dim,
+            array,
+        })
+    }
+
+    /// The cells in row-major order
+    pub fn as_slice(&self) -> &[T]
+    {
+        &self.array
+    }
+
+    pub fn into_vec(self) -> Vec<T>
+    {
+        self.array
+    }
+
+    /// Build a grid of the same dimensions with every cell passed through `f`
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U>
+    {
+        Grid
+        {
+            dim: self.dim,
+            array: self.array.iter().map(f).collect(),
+        }
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self
+    {
+        self.dim.boundary = boundary;
+        self
+    }
+
+    /// The grid's size and boundary, which can only change along with the cells
+    pub fn dim(&self) -> &GridDim
+    {
+        &self.dim
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary)
+    {
+        self.dim.boundary = boundary;
+    }
+
+    /// Return an iterator of all the points that are inbounds, 
+    /// after wrapping or reflecting them through the grid's boundary
+    pub fn valid_neighbors_of<'a>
+    (
+        &'a self, 
+        neighbors: impl Iterator<Item = Point2<isize>> + 'a,
+    ) -> impl Iterator<Item = Point2<isize>> + 'a
+    {
+        // Grid::neighbors_of(p)
+        neighbors
+        .filter_map(|e| self.dim.resolve(e))
+    }
+
+    pub fn index(&self, p: impl Into<Point2<isize>>) -> Option<&T>
+    {
+        self.try_index(p).ok()
+    }
+
+    pub fn index_mut(&mut self, p: impl Into<Point2<isize>>) -> Option<&mut T>
+    {
+        self.try_index_mut(p).ok()
+    }
+
+    pub fn try_index(&self, p: impl Into<Point2<isize>>) -> Result<&T, GridError>
+    {
+        let p = p.into();
+        let i = self.dim.index_map(p)?;
+        self.array.get(i).ok_or(GridError::OutOfBounds { point: p, dim: self.dim })
+    }
+
+    pub fn try_index_mut(&mut self, p: impl Into<Point2<isize>>) -> Result<&mut T, GridError>
+    {
+        let p = p.into();
+        let i = self.dim.index_map(p)?;
+        self.array.get_mut(i).ok_or(GridError::OutOfBounds { point: p, dim: self.dim })
+    }
+
+    /// Every cell along with its point, in row-major order
+    pub fn cells(&self) -> impl Iterator<Item = (Point2<isize>, &T)>
+    {
+        self.dim.points().zip(self.array.iter())
+    }
+
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = (Point2<isize>, &mut T)>
+    {
+        self.dim.points().zip(self.array.iter_mut())
+    }
+
+    /// A* search over 4-connected cells, only stepping onto cells that are `passable`.
+    /// Returns every cell on the path except `start`.
+    pub fn find_path_with_a_star_by
+    (
+        &self, 
+        start: Point2<isize>, 
+        end: Point2<isize>, 
+        passable: impl Fn(&T) -> bool,
+    ) -> Option<std::collections::HashSet<Point2<isize>>>
+    {
+        IsTopology::a_star(self, start, end, |p| passable(self.index(p).unwrap()))
+    }
+}
+
+impl Grid
+{
+    /// The 8 surrounding points of `p`. These may lie outside the grid, 
+    /// pass them through `valid_neighbors_of` to apply the boundary.
     pub fn neighbors_of(p: impl Into<Point2<isize>>) -> impl Iterator<Item = Point2<isize>>
     {
         let p = p.into();
@@ -102,22 +425,6 @@ impl Grid
         ].into_iter()
     }
 
-    /// Return an iterator of all the points that are inbounds
-    pub fn valid_neighbors_of<'a>
-    (
-        &'a self, 
-        neighbors: impl Iterator<Item = Point2<isize>> + 'a,
-    ) -> impl Iterator<Item = Point2<isize>> + 'a
-    {
-        // Grid::neighbors_of(p)
-        neighbors
-        .filter(|&e|
-        {
-            0 <= e.x && e.x < self.width &&
-            0 <= e.y && e.y < self.height
-        })
-    }
-
     pub fn distance_chebyshev(a: impl Into<Point2<isize>>, b: impl Into<Point2<isize>>) -> isize
     {
         let a = a.into();
@@ -134,15 +441,25 @@ impl Grid
         isize::abs(a.x - b.x) + isize::abs(a.y - b.y)
     }
 
-    pub fn index(&self, p: impl Into<Point2<isize>>) -> Option<&u8>
+    /// Count the live Moore neighbours of `p`, with cells past the edges decided by the boundary
+    pub fn sum_neighbors(&self, p: impl Into<Point2<isize>>) -> u8
     {
-        // &self.array[Self::index_map(self.width, self.height, i, j)]
-        self.array.get(self.index_map(p) as usize)
+        self.sum_neighbors_by(p, |v| v > 0)
     }
 
-    pub fn index_mut(&mut self, p: impl Into<Point2<isize>>) -> Option<&mut u8>
+    /// Count the Moore neighbours of `p` whose value is `alive`, with cells past the edges decided by the boundary
+    pub fn sum_neighbors_by(&self, p: impl Into<Point2<isize>>, alive: impl Fn(u8) -> bool) -> u8
     {
-        self.array.get_mut(self.dim.index_map(p) as usize)
+        Self::neighbors_of(p)
+        .fold(0, 
+        |acc, p|
+        {
+            acc + match self.dim.resolve(p)
+            {
+                Some(p) => alive(*self.index(p).unwrap()) as u8,
+                None => (self.boundary == Boundary::Alive) as u8,
+            }
+        })
     }
 
     pub fn sum_neighbors_with_outside_dead(&self, p: impl Into<Point2<isize>>) -> u8
@@ -178,97 +495,7 @@ impl Grid
 
     pub fn find_path_with_a_star(&self, start: Point2<isize>, end: Point2<isize>) -> Option<std::collections::HashSet<Point2<isize>>>
     {
-        use std::collections::{HashMap, HashSet};
-
-        let mut prev = HashMap::new();
-        prev.insert(start, start);
-
-
-        // #[derive(Eq, PartialEq, PartialOrd)]
-        // struct PointDist(Point2<isize>, isize);
-        // // impl PartialEq for PointDist
-        // // {
-        // //     fn eq(&self, other: &Self) -> bool 
-        // //     {
-        // //         self.1.eq(&other.1)
-        // //     }
-        // // }
-
-        // // impl PartialOrd for PointDist
-        // // {
-        // //     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> 
-        // //     {
-        // //         self.1.partial_cmp(&other.1)    
-        // //     }
-        // // }
-
-        // impl Ord for PointDist
-        // {
-        //     fn cmp(&self, other: &Self) -> std::cmp::Ordering 
-        //     {
-        //         // self.1.cmp(&other.1) // put max value on top
-        //         other.1.cmp(&self.1) // put min on top
-        //     }
-        // }
-
-        let mut g_distance_from_start_to_or_inf = 
-            HashMap::from([(start, 0)]);
-
-        let mut f_dist_from_to_end = 
-            HashMap::from([(start, Grid::distance_cityblock(start, end))]);
-
-        let mut frontier = HashSet::from([start]);
-
-        while !frontier.is_empty()
-        {
-            // find cheapest node
-            let mut current = None;
-            let mut cost = isize::MAX;
-            for &node in &frontier
-            {
-                let this_cost = *f_dist_from_to_end.get(&node).unwrap_or(&isize::MAX);
-                if this_cost < cost
-                {
-                    current = Some(node);
-                    cost = this_cost;
-                }
-            }
-
-            let current = current.unwrap();
-            if current == end
-            {
-                return Some(Grid::trace_back(start, end, &prev));
-            }
-
-            frontier.remove(&current);
-
-            // for neighbor in self.valid_neighbors_of(current)
-            for neighbor in self.valid_neighbors_of(Grid::neighbors_of_limited(current))
-            {
-                if *self.index(neighbor).unwrap() > 0 { continue }
-
-                let tentative_g_score = 
-                    g_distance_from_start_to_or_inf.get(&current).unwrap_or(&(isize::MAX-1)) + 1;
-                if tentative_g_score < *g_distance_from_start_to_or_inf.get(&neighbor).unwrap_or(&(isize::MAX))
-                {
-                    prev.insert(neighbor, current);
-                    g_distance_from_start_to_or_inf.insert(neighbor, tentative_g_score);
-                    f_dist_from_to_end.insert(neighbor, tentative_g_score + Grid::distance_cityblock(neighbor, end));
-
-                    if !frontier.contains(&neighbor)
-                    {
-                        frontier.insert(neighbor);
-                    }
-                }
-            }
-        }
-
-        // we need a priority queue of points with estimates of distances to the end
-        // g(n) is the distance from start to curr, h(n) is an estimate of distance from curr to end
-        
-        
-        
-        None
+        self.find_path_with_a_star_by(start, end, |&v| v == 0)
     }
 
     pub fn find_path_of_zeroes(&self, start: Point2<isize>, end: Point2<isize>) -> Option<std::collections::HashSet<Point2<isize>>>
@@ -309,10 +536,38 @@ impl Grid
     }    
 }
 
+impl<T> IsTopology for Grid<T>
+{
+    type Point = Point2<isize>;
+
+    fn adjacent(&self, p: Self::Point) -> impl Iterator<Item = Self::Point>
+    {
+        self.valid_neighbors_of(Grid::neighbors_of_limited(p))
+    }
+
+    fn distance(&self, a: Self::Point, b: Self::Point) -> isize
+    {
+        self.cityblock_through_boundary(a, b)
+    }
+}
+
 pub trait IsRules
 {
     fn get_birth(&self)     -> impl Iterator<Item = &u8>;
     fn get_surive(&self)    -> impl Iterator<Item = &u8>;
+
+    /// How many states a cell can be in: dead, alive, and for Generations rules the decay states after alive.
+    /// Only the byte `Grid` automaton steps decay states, the other steppers need 2.
+    fn states(&self) -> u8
+    {
+        2
+    }
+
+    /// Compile the rule into masks, which steppers build once per step instead of scanning the rule per cell
+    fn table(&self) -> RuleTable
+    {
+        RuleTable::new(self)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -357,6 +612,7 @@ pub struct DynamicRules
 {
     birth:      Vec<u8>,
     survive:    Vec<u8>,
+    states:     u8,
 }
 
 impl IsRules for DynamicRules
@@ -370,6 +626,11 @@ impl IsRules for DynamicRules
     {
         self.birth.iter()
     }
+
+    fn states(&self) -> u8
+    {
+        self.states
+    }
 }
 
 impl DynamicRules
@@ -380,79 +641,133 @@ impl DynamicRules
         {
             birth:      Vec::from(birth),
             survive:    Vec::from(survive),
+            states:     2,
         }
     }
+
+    /// Turn this into a Generations rule with `states` states, where live cells that fail to survive
+    /// pass through the decay states `2..states` before they're dead and can be born again
+    pub fn with_states(mut self, states: u8) -> Self
+    {
+        assert!(states >= 2);
+        self.states = states;
+        self
+    }
 }
 
+/// Reads `B3/S23` (or `B3S23`), Generations rules like `B2/S/C3`,
+/// and Golly's letterless survive/birth forms `23/3` and `345/2/4`.
+///
+/// Each digit is one count unless the counts are separated by commas, which allows counts of 10 and up
+/// for the 12 neighbour triangle grid or 26 neighbour 3D grid: `B4,10,12/S2,3`, or `B10,/S` for a lone 10.
 impl std::str::FromStr for DynamicRules
 {
     type Err = String;
     fn from_str(s_in: &str) -> Result<Self, Self::Err> 
     {
-        // find index of B, find index of S
-        let b_loc = s_in.chars().enumerate().find(|c|c.1.to_ascii_lowercase() == 'b');
-        let s_loc = s_in.chars().enumerate().find(|c| c.1.to_ascii_lowercase() == 's');
-
-
-
-
-        if let (Some((b_loc, _)), Some((s_loc, _))) = (b_loc, s_loc)
-        {
-            // B123S123
-            // 01234567
-            // while let Some(d) = s_in.chars().skip(b_loc+1).map(|c| c.to_digit(10))
-            // {
-
-            // }
-
-            // FIXME: Why doesn't fuse work to stop after first failed to_digit?
-            let birth_rules = 
-                s_in.chars().take(s_loc - b_loc - 1)
-                .skip(b_loc+1)
-                .map(|c| c.to_digit(10))
-                .fuse()
-                .flatten()
-                .map(|digit| digit as u8)
-                .collect::<Vec<u8>>();
-
-            let survive_rules = 
-                s_in.chars()
-                .skip(s_loc+1)
-                .map(|c| c.to_digit(10))
-                .fuse()
-                .flatten()
-                .map(|d| d as u8)
-                .collect::<Vec<u8>>();
-
-            // for c in s_in.chars().skip(b_loc+1).take(s_loc - b_loc - 1)
-            // {
-            //     let n: u8 = 
-            //         c.to_digit(10)
-            //         .ok_or(String::from("digit unable to be parsed"))? as u8;
-            //     birth_rules.push(n);
-            // }
-
-            // let mut survive_rules = Vec::new();
-            // // B123S123
-            // // 01234567
-            // for c in s_in.chars().skip(s_loc+1)
-            // {
-            //     let n: u8 = 
-            //         c.to_digit(10)
-            //         .ok_or(String::from("digit unable to be parsed"))? as u8;
-            //     survive_rules.push(n);
-            // }
-            
-            // let birth_rules = Vec::new();
-            
-            // let survive_rules = Vec::new();
-
-            return Ok(DynamicRules::new(&birth_rules, &survive_rules));
+        let s = s_in.trim();
+        let digits = |part: &str| -> Result<Vec<u8>, String>
+        {
+            if part.contains(',')
+            {
+                return part
+                    .split(',')
+                    .filter(|n| !n.is_empty())
+                    .map(|n| n.parse::<u8>().map_err(|_| format!("{:?} is not a neighbour count", n)))
+                    .collect();
+            }
+
+            part.chars()
+            .map(|c| c.to_digit(10).map(|d| d as u8).ok_or(format!("unexpected {:?} in rule string", c)))
+            .collect()
+        };
+        let states = |part: &str| -> Result<u8, String>
+        {
+            match part.parse::<u8>()
+            {
+                Ok(n) if n >= 2 => Ok(n),
+                _ => Err(format!("{:?} is not a number of states from 2 to 255", part)),
+            }
+        };
+
+        // letterless rules list survive counts first
+        if !s.contains(['B', 'b', 'S', 's'])
+        {
+            let parts: Vec<&str> = s.split('/').collect();
+            return match parts[..]
+            {
+                [survive, birth] => Ok(DynamicRules::new(&digits(birth)?, &digits(survive)?)),
+                [survive, birth, n] => Ok(DynamicRules::new(&digits(birth)?, &digits(survive)?).with_states(states(n)?)),
+                _ => Err(String::from("B or S not present in rule string")),
+            };
         }
-        else
+
+        let (mut birth, mut survive, mut generations) = (None, None, None);
+        let mut section: Option<&mut String> = None;
+        let mut at_part_start = true;
+        for c in s.chars()
+        {
+            match c
+            {
+                'B' | 'b' => section = Some(birth.insert(String::new())),
+                'S' | 's' => section = Some(survive.insert(String::new())),
+                // the number of states only ever opens a part, like the `C3` in `B2/S/C3`
+                'C' | 'c' | 'G' | 'g' if at_part_start => section = Some(generations.insert(String::new())),
+                '0'..='9' | ',' => match section.as_mut()
+                {
+                    Some(counts) => counts.push(c),
+                    None => return Err(format!("{:?} comes before B or S in rule string", c)),
+                },
+                '/' => (),
+                // hexagonal rules end in H, the grid decides the neighbourhood instead
+                'H' | 'h' => (),
+                c => return Err(format!("unexpected {:?} in rule string", c)),
+            }
+            at_part_start = c == '/';
+        }
+
+        match (birth, survive)
+        {
+            (Some(birth), Some(survive)) =>
+            {
+                let rules = DynamicRules::new(&digits(&birth)?, &digits(&survive)?);
+                match generations
+                {
+                    Some(n) => Ok(rules.with_states(states(&n)?)),
+                    None => Ok(rules),
+                }
+            },
+            _ => Err(String::from("B or S not present in rule string")),
+        }
+    }
+}
+
+/// Writes the rule as a `B3/S23` string, or `B2/S/C3` for Generations rules, which `from_str` reads back.
+/// Counts of 10 and up switch their section to commas.
+impl std::fmt::Display for DynamicRules
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let counts = |counts: &[u8]| -> String
+        {
+            if counts.iter().all(|&n| n < 10)
+            {
+                return counts.iter().map(|n| n.to_string()).collect();
+            }
+
+            // a lone count still needs a comma so it isn't read as digits
+            let joined = counts.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+            if counts.len() == 1 { joined + "," } else { joined }
+        };
+
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survive))?;
+
+        if self.states > 2
         {
-            return Err(String::from("B or S not present in rule string"));
+            write!(f, "/C{}", self.states)?;
         }
+
+        Ok(())
     }
 }
 
@@ -464,69 +779,188 @@ impl<const B: usize, const S: usize> From<ConstRules<B, S>> for DynamicRules
     }
 }
 
-pub struct Automaton<R>
+/// Steps `rules` over a cell storage `G`, which is a byte-per-cell `Grid` unless
+/// another backend such as [`BitGrid`] is picked.
+pub struct Automaton<R, G = Grid>
 {
-    pub grid: Grid,
-    other_grid: Grid,
+    pub grid: G,
+    other_grid: G,
     pub rules: R,
+    generation: u64,
+    /// Weights for counting neighbours in place of the 8 surrounding cells, only used by the byte `Grid` automaton
+    kernel: Option<Kernel<u32>>,
 }
 
-impl<R> Automaton<R>
+impl<R, G> Automaton<R, G>
 where
-    R: IsRules
+    G: Clone
 {
-    pub fn new(grid: Grid, rules: R) -> Self
+    pub fn new(grid: G, rules: R) -> Self
     {
         Automaton
         {
             grid: grid.clone(),
             other_grid: grid,
             rules,
+            generation: 0,
+            kernel: None,
+        }
+    }
+
+    /// How many times the automaton has been stepped
+    pub fn generation(&self) -> u64
+    {
+        self.generation
+    }
+}
+
+/// An automaton that can be advanced one generation, which is all `step_n` and `run_until` need
+pub trait IsStepper
+{
+    fn step(&mut self);
+}
+
+impl<R, G> Automaton<R, G>
+where
+    Self: IsStepper
+{
+    pub fn step_n(&mut self, n: u64)
+    {
+        for _ in 0..n
+        {
+            IsStepper::step(self);
+        }
+    }
+
+    /// Step until `done` returns true, checking before every step, and return how many steps were taken.
+    /// `done` can give up after a while by looking at `generation()`.
+    pub fn run_until(&mut self, mut done: impl FnMut(&Self) -> bool) -> u64
+    {
+        let start = self.generation;
+        while !done(self)
+        {
+            IsStepper::step(self);
         }
+
+        self.generation - start
     }
+}
 
+impl<R> Automaton<R>
+{
     pub fn new_from_dims(width: isize, height: isize, rules: R) -> Self
     {
         let grid = Grid::new(width, height);
         Automaton::new(grid, rules)
     }
 
+    pub fn set_boundary(&mut self, boundary: Boundary)
+    {
+        self.grid.set_boundary(boundary);
+        self.other_grid.set_boundary(boundary);
+    }
+
+    /// get the spare buffer ready to be written over
+    fn prepare_other_grid(&mut self)
+    {
+        // `grid` is public, so it may have been swapped for one of another size since the last step
+        if self.other_grid.array.len() != self.grid.array.len()
+        {
+            self.other_grid = self.grid.clone();
+        }
+        self.other_grid.dim = self.grid.dim;
+    }
+}
+
+impl<R> Automaton<R>
+where
+    R: IsRules
+{
+    /// Count neighbours as the weighted sum under `kernel` instead of the 8 surrounding cells,
+    /// for weighted neighbourhoods or larger radii
+    pub fn set_kernel(&mut self, kernel: Kernel<u32>)
+    {
+        self.kernel = Some(kernel);
+    }
+
+    /// Advance one generation, writing into the spare buffer and swapping it in
     pub fn step(&mut self)
     {
-        for y in 0..self.grid.height
+        let table = self.rules.table();
+        let counts = self.kernel_counts(&table);
+        self.prepare_other_grid();
+
+        for p in self.grid.points()
+        {
+            *self.other_grid.index_mut(p).unwrap() = next_cell(&self.grid, &table, counts.as_ref(), p);
+        }
+
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+
+    /// Same as `step`, with the rows split into `threads` stripes that are stepped at once.
+    /// `std::thread::available_parallelism` is a good default for `threads`.
+    pub fn step_parallel(&mut self, threads: usize)
+    {
+        assert!(threads > 0);
+
+        let table = self.rules.table();
+        let counts = self.kernel_counts(&table);
+        self.prepare_other_grid();
+
+        let width = self.grid.width as usize;
+        let stripe_rows = (self.grid.height as usize).div_ceil(threads);
+        let (grid, table, counts) = (&self.grid, &table, counts.as_ref());
+
+        std::thread::scope(|scope|
         {
-            for x in 0..self.grid.width
+            for (i, stripe) in self.other_grid.array.chunks_mut(stripe_rows * width).enumerate()
             {
-                let p = Point2 { x, y };
-                let num_alive_neighbors = self.grid.sum_neighbors_with_outside_dead(p);
-                match self.grid.index(p).unwrap()
+                scope.spawn(move ||
                 {
-                    // dead
-                    0 if self.rules.get_birth().any(|&e| e == num_alive_neighbors) =>
+                    let first = i * stripe_rows * width;
+                    for (j, cell) in stripe.iter_mut().enumerate()
                     {
-                        *self.other_grid.index_mut(p).unwrap() = 1;
-                    },
-                    // alive
-                    1.. if !self.rules.get_surive().any(|&e|e == num_alive_neighbors) =>
-                    {
-                        *self.other_grid.index_mut(p).unwrap() = 0; // set to 1 for some cool effects
-                    },
-                    _ => (),
-                };
+                        let k = first + j;
+                        *cell = next_cell(grid, table, counts, Point2 { x: (k % width) as isize, y: (k / width) as isize });
+                    }
+                });
             }
-        }
+        });
 
-        self.grid = self.other_grid.clone();
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+
+    /// neighbour counts under the custom kernel, if there is one
+    fn kernel_counts(&self, table: &RuleTable) -> Option<Grid<u32>>
+    {
+        self.kernel.as_ref().map(|k| self.grid.map(|&v| table.alive(v) as u8).convolve(k))
     }
 }
 
+impl_stepper!(<R: IsRules> Automaton<R>);
+
+/// The value the cell at `p` steps to, counting neighbours from `counts` when a kernel is in use
+fn next_cell(grid: &Grid, table: &RuleTable, counts: Option<&Grid<u32>>, p: Point2<isize>) -> u8
+{
+    let num_alive_neighbors = match counts
+    {
+        Some(counts) => *counts.index(p).unwrap(),
+        None => grid.sum_neighbors_by(p, |v| table.alive(v)) as u32,
+    };
+
+    table.next_state(*grid.index(p).unwrap(), num_alive_neighbors)
+}
+
 fn _main()
 {
     let mut at = Automaton::new_from_dims(32, 32, ConstRules::<1, 0>::SEEDS);
-    at.grid.array[100] = 1;
-    at.grid.array[101] = 1;
-    at.grid.array[0] = 1;
-    at.grid.array[1] = 1;
+    *at.grid.index_mut([4, 3]).unwrap() = 1;
+    *at.grid.index_mut([5, 3]).unwrap() = 1;
+    *at.grid.index_mut([0, 0]).unwrap() = 1;
+    *at.grid.index_mut([1, 0]).unwrap() = 1;
 
     println!("{}", at.grid);
     at.step();
@@ -552,4 +986,198 @@ fn _main()
     }
 
     println!("{}", at.grid);
-}
\ No newline at end of file
+}
+#[cfg(test)]
+pub(crate) mod tests
+{
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    /// a `width * height` grid with the middle `soup * soup` cells set at random,
+    /// and a dead margin around them for patterns to grow into
+    pub(crate) fn soup(width: isize, height: isize, soup: isize, seed: u64) -> Grid
+    {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut grid = Grid::new(width, height);
+        let (x0, y0) = ((width - soup) / 2, (height - soup) / 2);
+        for y in y0..y0 + soup
+        {
+            for x in x0..x0 + soup
+            {
+                *grid.index_mut([x, y]).unwrap() = rng.gen_bool(0.4) as u8;
+            }
+        }
+
+        grid
+    }
+
+    #[test]
+    fn try_index_rejects_points_outside_the_grid()
+    {
+        let grid = Grid::<u8>::new(2, 2);
+        assert!(matches!(grid.try_index([2, 0]), Err(GridError::OutOfBounds { .. })));
+        assert!(matches!(grid.try_index([0, -1]), Err(GridError::OutOfBounds { .. })));
+        assert_eq!(grid.try_index([1, 1]), Ok(&0));
+    }
+
+    #[test]
+    fn try_from_vec_rejects_sizes_that_overflow()
+    {
+        assert!(matches!(Grid::<u8>::try_from_vec(isize::MAX, 2, vec![]), Err(GridError::InvalidSize { .. })));
+        assert!(matches!(Grid::<u8>::try_from_vec(-2, -2, vec![0; 4]), Err(GridError::InvalidSize { .. })));
+        assert!(matches!(Grid::try_from_vec(2, 2, vec![0u8; 3]), Err(GridError::LengthMismatch { expected: 4, found: 3 })));
+        assert!(Grid::try_from_vec(2, 2, vec![0u8; 4]).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "grid area overflows isize")]
+    fn new_rejects_sizes_that_overflow()
+    {
+        Grid::<u8>::new(isize::MAX, 2);
+    }
+
+    #[test]
+    fn try_index_checks_the_cells_even_if_dim_is_wrong()
+    {
+        let mut grid = Grid::<u8>::new(2, 2);
+        grid.dim.width = 100;
+        assert!(matches!(grid.try_index([50, 0]), Err(GridError::OutOfBounds { .. })));
+        assert!(grid.try_index_mut([50, 0]).is_err());
+    }
+
+    #[test]
+    fn gliders_come_back_round_a_torus()
+    {
+        let glider: Grid = ".O......\n..O.....\nOOO.....\n........\n........\n........\n........\n........".parse().unwrap();
+        let mut at = Automaton::new(glider.clone().with_boundary(Boundary::Wrap), ConstRules::new([3], [2, 3]));
+
+        // a glider moves one cell diagonally every 4 generations
+        at.step_n(4 * 8);
+        assert_eq!(at.grid.as_slice(), glider.as_slice());
+    }
+
+    #[test]
+    fn alive_edges_give_birth_along_the_sides()
+    {
+        let mut at = Automaton::new(Grid::new(5, 4).with_boundary(Boundary::Alive), ConstRules::new([3], [2, 3]));
+        at.step();
+
+        // cells along a side see 3 live cells past it, corners see 5
+        let expected: Grid = ".OOO.\nO...O\nO...O\n.OOO.".parse().unwrap();
+        assert_eq!(at.grid.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn distance_is_the_shortest_walk_through_the_boundary()
+    {
+        for boundary in [Boundary::Dead, Boundary::Alive, Boundary::Wrap, Boundary::Mirror, Boundary::Klein]
+        {
+            let grid = Grid::<u8>::new(7, 6).with_boundary(boundary);
+            for a in grid.points()
+            {
+                let mut steps = std::collections::HashMap::from([(a, 0)]);
+                let mut queue = std::collections::VecDeque::from([a]);
+                while let Some(p) = queue.pop_front()
+                {
+                    for n in grid.adjacent(p)
+                    {
+                        if !steps.contains_key(&n)
+                        {
+                            steps.insert(n, steps[&p] + 1);
+                            queue.push_back(n);
+                        }
+                    }
+                }
+
+                for (b, steps) in steps
+                {
+                    assert_eq!(grid.distance(a, b), steps, "{:?} from {:?} to {:?}", boundary, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_steps_match_plain_steps()
+    {
+        let grid = soup(61, 47, 47, 3).with_boundary(Boundary::Wrap);
+        let rules: [DynamicRules; 2] = ["B3/S23".parse().unwrap(), "B2/S345/C4".parse().unwrap()];
+
+        for rules in rules
+        {
+            for kernel in [None, Some(Kernel::von_neumann(2))]
+            {
+                // more threads than rows leaves some stripes empty
+                for threads in [1, 2, 7, 100]
+                {
+                    let mut plain = Automaton::new(grid.clone(), rules.clone());
+                    let mut parallel = Automaton::new(grid.clone(), rules.clone());
+                    if let Some(kernel) = &kernel
+                    {
+                        plain.set_kernel(kernel.clone());
+                        parallel.set_kernel(kernel.clone());
+                    }
+
+                    for _ in 0..6
+                    {
+                        plain.step();
+                        parallel.step_parallel(threads);
+                        assert_eq!(parallel.grid.as_slice(), plain.grid.as_slice(), "{} threads", threads);
+                    }
+                    assert_eq!(parallel.generation(), plain.generation());
+                }
+            }
+        }
+    }
+
+    fn counts(rules: &DynamicRules) -> (Vec<u8>, Vec<u8>)
+    {
+        (rules.get_birth().copied().collect(), rules.get_surive().copied().collect())
+    }
+
+    #[test]
+    fn rule_strings_parse()
+    {
+        let parse = |s: &str| counts(&s.parse::<DynamicRules>().unwrap());
+
+        assert_eq!(parse("B3/S23"), (vec![3], vec![2, 3]));
+        assert_eq!(parse("b36s23"), (vec![3, 6], vec![2, 3]));
+        assert_eq!(parse("23/3"), (vec![3], vec![2, 3]));
+        assert_eq!(parse("B2/S34H"), (vec![2], vec![3, 4]));
+        assert_eq!("B2/S/C3".parse::<DynamicRules>().unwrap().states(), 3);
+        assert_eq!("345/2/4".parse::<DynamicRules>().unwrap().states(), 4);
+    }
+
+    #[test]
+    fn rule_strings_take_counts_past_9_with_commas()
+    {
+        let parse = |s: &str| counts(&s.parse::<DynamicRules>().unwrap());
+
+        // up to 12 for triangle vertices, up to 26 for 3D Moore
+        assert_eq!(parse("B4,10,12/S2,3"), (vec![4, 10, 12], vec![2, 3]));
+        assert_eq!(parse("B10,/S11,"), (vec![10], vec![11]));
+        assert_eq!(parse("B11/S12,"), (vec![1, 1], vec![12]));
+        assert_eq!(parse("B13,14,15/S5,6,7,12,13,15"), (vec![13, 14, 15], vec![5, 6, 7, 12, 13, 15]));
+        assert_eq!(parse("10,11/12,"), (vec![12], vec![10, 11]));
+    }
+
+    #[test]
+    fn rule_strings_round_trip()
+    {
+        for s in ["B3/S23", "B/S", "B2/S/C3", "B4,10,12/S23", "B10,/S2,12", "B2/S12,/C5"]
+        {
+            let rules: DynamicRules = s.parse().unwrap();
+            assert_eq!(rules.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn bad_rule_strings_are_rejected()
+    {
+        for s in ["3", "B3", "B3x/S23", "B3/S23/C1", "B3/S23/C256", "B256,/S", "B1,,x/S"]
+        {
+            assert!(s.parse::<DynamicRules>().is_err(), "{}", s);
+        }
+    }
+}
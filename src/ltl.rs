@@ -0,0 +1,360 @@
+use std::ops::RangeInclusive;
+
+use crate::{table::advance, Automaton, Boundary, Grid};
+
+/// The shape of a Larger-than-Life neighbourhood, the `N` field of a rule string
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LtlNeighborhood
+{
+    /// The `2 * range + 1` square, `NM`
+    #[default]
+    Moore,
+    /// Cells within `range` cityblock steps, `NN`
+    VonNeumann,
+    /// Cells whose centres lie within `range + 1/2` of the cell's centre, `NC`
+    Circular,
+}
+
+impl LtlNeighborhood
+{
+    /// How far the neighbourhood reaches left and right on the row `dy` above or below the cell
+    pub fn half_width(self, range: isize, dy: isize) -> isize
+    {
+        match self
+        {
+            LtlNeighborhood::Moore => range,
+            LtlNeighborhood::VonNeumann => range - dy.abs(),
+            // x² + dy² < (range + 1/2)², which for whole numbers is x² + dy² <= range² + range
+            LtlNeighborhood::Circular => (range * range + range - dy * dy).isqrt(),
+        }
+    }
+}
+
+/// Larger-than-Life rules: a cell is born or survives when the live cells within `range`
+/// fall in an interval, read from and written as Kellie Evans' `R5,C0,M1,S34..58,B34..45,NM` notation
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LtlRules
+{
+    pub range: isize,
+    /// 2 for live and dead only, above that dying cells decay like Generations rules
+    pub states: u8,
+    /// Whether a cell counts itself among its neighbours
+    pub middle: bool,
+    pub survive: RangeInclusive<u32>,
+    pub birth: RangeInclusive<u32>,
+    pub neighborhood: LtlNeighborhood,
+}
+
+impl LtlRules
+{
+    /// Bosco's rule, which grows smooth blobby caves out of noise
+    pub const BOSCO: LtlRules = LtlRules
+    {
+        range: 5,
+        states: 2,
+        middle: true,
+        survive: RangeInclusive::new(34, 58),
+        birth: RangeInclusive::new(34, 45),
+        neighborhood: LtlNeighborhood::Moore,
+    };
+
+    /// Two-state rules over the Moore neighbourhood, not counting the cell itself
+    pub fn new(range: isize, birth: RangeInclusive<u32>, survive: RangeInclusive<u32>) -> Self
+    {
+        assert!(range >= 1);
+
+        LtlRules
+        {
+            range,
+            states: 2,
+            middle: false,
+            survive,
+            birth,
+            neighborhood: LtlNeighborhood::Moore,
+        }
+    }
+
+    pub fn with_neighborhood(mut self, neighborhood: LtlNeighborhood) -> Self
+    {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    pub fn with_middle(mut self, middle: bool) -> Self
+    {
+        self.middle = middle;
+        self
+    }
+
+    pub fn with_states(mut self, states: u8) -> Self
+    {
+        assert!(states >= 2);
+        self.states = states;
+        self
+    }
+
+    /// The state a cell is in next generation, given its state now and its live neighbour count
+    pub fn next_state(&self, state: u8, count: u32) -> u8
+    {
+        let interval = if state == 0 { &self.birth } else { &self.survive };
+        advance(self.states, state, interval.contains(&count))
+    }
+}
+
+impl std::str::FromStr for LtlRules
+{
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let number = |field: &str, value: &str| -> Result<u32, String>
+        {
+            value.trim().parse().map_err(|_| format!("{:?} is not a number in the {} field", value, field))
+        };
+        let interval = |field: &str, value: &str| -> Result<RangeInclusive<u32>, String>
+        {
+            match value.split_once("..")
+            {
+                Some((low, high)) => Ok(number(field, low)?..=number(field, high)?),
+                None => Ok(number(field, value)?..=number(field, value)?),
+            }
+        };
+
+        let (mut range, mut birth, mut survive) = (None, None, None);
+        let mut rules = LtlRules::new(1, 0..=0, 0..=0);
+        for part in s.trim().split(',')
+        {
+            let part = part.trim();
+            let mut chars = part.chars();
+            let Some(field) = chars.next()
+            else
+            {
+                return Err(String::from("empty field in rule string"));
+            };
+            let value = chars.as_str();
+
+            match field.to_ascii_uppercase()
+            {
+                'R' => range = Some(number("R", value)?),
+                'C' => rules.states = match number("C", value)?
+                {
+                    // C0 and C2 both mean plain live and dead cells
+                    0 | 2 => 2,
+                    n @ 3..=255 => n as u8,
+                    n => return Err(format!("{} is not a number of states", n)),
+                },
+                'M' => rules.middle = match value
+                {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err(format!("{:?} is not 0 or 1 in the M field", value)),
+                },
+                'S' => survive = Some(interval("S", value)?),
+                'B' => birth = Some(interval("B", value)?),
+                'N' => rules.neighborhood = match value
+                {
+                    "M" | "m" => LtlNeighborhood::Moore,
+                    "N" | "n" => LtlNeighborhood::VonNeumann,
+                    "C" | "c" => LtlNeighborhood::Circular,
+                    _ => return Err(format!("{:?} is not a neighbourhood of M, N or C", value)),
+                },
+                _ => return Err(format!("unexpected field {:?} in rule string", part)),
+            }
+        }
+
+        match (range, birth, survive)
+        {
+            (Some(range @ 1..=500), Some(birth), Some(survive)) => Ok(LtlRules { range: range as isize, birth, survive, ..rules }),
+            (Some(range), Some(_), Some(_)) => Err(format!("range {} is not from 1 to 500", range)),
+            _ => Err(String::from("R, B or S not present in rule string")),
+        }
+    }
+}
+
+/// Writes the rule as a `R5,C0,M1,S34..58,B34..45,NM` string, which `from_str` reads back
+impl std::fmt::Display for LtlRules
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let states = if self.states > 2 { self.states } else { 0 };
+        let neighborhood = match self.neighborhood
+        {
+            LtlNeighborhood::Moore => 'M',
+            LtlNeighborhood::VonNeumann => 'N',
+            LtlNeighborhood::Circular => 'C',
+        };
+
+        write!(f, "R{},C{},M{},", self.range, states, self.middle as u8)?;
+        write!(f, "S{}..{},", self.survive.start(), self.survive.end())?;
+        write!(f, "B{}..{},", self.birth.start(), self.birth.end())?;
+        write!(f, "N{}", neighborhood)
+    }
+}
+
+impl Grid
+{
+    /// Live cells in each cell's Larger-than-Life neighbourhood, with cells past the edges decided by the boundary.
+    ///
+    /// Every row of the neighbourhood is read off running sums along the grid's rows,
+    /// so a cell costs `2 * range + 1` lookups rather than one per neighbour.
+    pub fn ltl_neighbor_counts(&self, rules: &LtlRules) -> Grid<u32>
+    {
+        let r = rules.range;
+        let (w, h) = (self.width, self.height);
+        let alive = |v: u8| if rules.states > 2 { v == 1 } else { v > 0 };
+        let outside = (self.boundary == Boundary::Alive) as u32;
+
+        // sums[py][px] holds the live cells left of px on row py of the grid padded by the range,
+        // so any stretch of a row is the difference of two sums
+        let stride = (w + 2 * r + 1) as usize;
+        let mut sums = vec![0u32; stride * (h + 2 * r) as usize];
+        for (py, row) in sums.chunks_mut(stride).enumerate()
+        {
+            for px in 0..stride - 1
+            {
+                let v = match self.dim.resolve([px as isize - r, py as isize - r])
+                {
+                    Some(q) => alive(*self.index(q).unwrap()) as u32,
+                    None => outside,
+                };
+                row[px + 1] = row[px] + v;
+            }
+        }
+
+        let spans: Vec<isize> = (-r..=r).map(|dy| rules.neighborhood.half_width(r, dy)).collect();
+
+        let array = self
+            .points()
+            .map(|p|
+            {
+                let count = spans.iter().enumerate().fold(0, |acc, (i, &half)|
+                {
+                    let row = (p.y as usize + i) * stride;
+                    // the cell's column sits `r` in from the left of the padded row
+                    let (left, right) = ((p.x + r - half) as usize, (p.x + r + half + 1) as usize);
+                    acc + sums[row + right] - sums[row + left]
+                });
+
+                match rules.middle
+                {
+                    true => count,
+                    false => count - alive(*self.index(p).unwrap()) as u32,
+                }
+            })
+            .collect();
+
+        Grid::from_vec(w, h, array).with_boundary(self.boundary)
+    }
+}
+
+impl Automaton<LtlRules>
+{
+    /// Step every cell by how many cells are alive in its range, summed once per generation from running row sums.
+    /// Survivors keep their value, and rules with more than 2 states decay cells the same as on a `Grid`.
+    pub fn step(&mut self)
+    {
+        let counts = self.grid.ltl_neighbor_counts(&self.rules);
+        self.prepare_other_grid();
+
+        for p in self.grid.points()
+        {
+            *self.other_grid.index_mut(p).unwrap() = self.rules.next_state(*self.grid.index(p).unwrap(), *counts.index(p).unwrap());
+        }
+
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+}
+
+impl_stepper!(Automaton<LtlRules>);
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{tests::soup, ConstRules};
+
+    #[test]
+    fn range_1_steps_like_life()
+    {
+        let grid = soup(40, 40, 40, 5);
+        for boundary in [Boundary::Dead, Boundary::Alive, Boundary::Wrap, Boundary::Mirror, Boundary::Klein]
+        {
+            let mut life = Automaton::new(grid.clone().with_boundary(boundary), ConstRules::new([3], [2, 3]));
+            let mut ltl = Automaton::new(grid.clone().with_boundary(boundary), LtlRules::new(1, 3..=3, 2..=3));
+
+            for _ in 0..8
+            {
+                life.step();
+                ltl.step();
+                assert_eq!(ltl.grid.as_slice(), life.grid.as_slice(), "{:?}", boundary);
+            }
+        }
+    }
+
+    #[test]
+    fn running_sums_count_every_neighbour()
+    {
+        let grid = soup(23, 23, 23, 6);
+        for neighborhood in [LtlNeighborhood::Moore, LtlNeighborhood::VonNeumann, LtlNeighborhood::Circular]
+        {
+            for boundary in [Boundary::Alive, Boundary::Klein]
+            {
+                let grid = grid.clone().with_boundary(boundary);
+                let rules = LtlRules::new(3, 0..=0, 0..=0).with_neighborhood(neighborhood).with_middle(true);
+                let counts = grid.ltl_neighbor_counts(&rules);
+
+                for p in grid.points()
+                {
+                    let mut count = 0;
+                    for dy in -3..=3isize
+                    {
+                        let half = neighborhood.half_width(3, dy);
+                        for dx in -half..=half
+                        {
+                            count += grid.alive_through_boundary([p.x + dx, p.y + dy].into()) as u32;
+                        }
+                    }
+                    assert_eq!(*counts.index(p).unwrap(), count, "{:?} {:?} at {:?}", neighborhood, boundary, p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rule_strings_round_trip()
+    {
+        assert_eq!("R5,C0,M1,S34..58,B34..45,NM".parse::<LtlRules>().unwrap(), LtlRules::BOSCO);
+
+        let rules = LtlRules::new(7, 10..=20, 5..=40)
+            .with_neighborhood(LtlNeighborhood::Circular)
+            .with_states(5);
+        assert_eq!(rules.to_string().parse::<LtlRules>().unwrap(), rules);
+
+        // fields may come in any order, and B and S may be single counts
+        let rules: LtlRules = "b3, s2..3, r1".parse().unwrap();
+        assert_eq!(rules, LtlRules::new(1, 3..=3, 2..=3));
+    }
+
+    #[test]
+    fn bad_rule_strings_are_rejected()
+    {
+        let bad = [
+            "",
+            "R5,B3",
+            "R0,B3,S2",
+            "R501,B3,S2",
+            "R5,C1,B3,S2",
+            "R5,C256,B3,S2",
+            "R5,M2,B3,S2",
+            "R5,NX,B3,S2",
+            "R5,B3..x,S2",
+            "R5,Q1,B3,S2",
+            "R5,,B3,S2",
+        ];
+
+        for s in bad
+        {
+            assert!(s.parse::<LtlRules>().is_err(), "{:?}", s);
+        }
+    }
+}
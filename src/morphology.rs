@@ -0,0 +1,232 @@
+use mint::Point2;
+
+use crate::{Boundary, Grid};
+
+/// The shape `erode`, `dilate` and friends probe each cell with
+#[derive(Clone, Debug)]
+pub enum StructuringElement
+{
+    /// A plus sign reaching `radius` cells up, down, left and right
+    Cross(isize),
+    /// A `2 * radius + 1` wide square
+    Square(isize),
+    /// Every non-zero cell of the mask, centred on the mask's middle cell (rounding down and right)
+    Custom(Grid),
+}
+
+impl Default for StructuringElement
+{
+    fn default() -> Self
+    {
+        StructuringElement::Cross(1)
+    }
+}
+
+impl StructuringElement
+{
+    /// Offsets from the centre to each point of the element
+    pub fn offsets(&self) -> Vec<Point2<isize>>
+    {
+        match self
+        {
+            StructuringElement::Cross(r) =>
+            {
+                (-r..=*r)
+                .flat_map(|dy| (-r..=*r).map(move |dx| (dx, dy)))
+                .filter(|&(dx, dy)| dx == 0 || dy == 0)
+                .map(|(x, y)| Point2 { x, y })
+                .collect()
+            },
+            StructuringElement::Square(r) =>
+            {
+                (-r..=*r)
+                .flat_map(|dy| (-r..=*r).map(move |dx| Point2 { x: dx, y: dy }))
+                .collect()
+            },
+            StructuringElement::Custom(mask) =>
+            {
+                let (cx, cy) = (mask.width / 2, mask.height / 2);
+                mask.cells()
+                .filter(|(_, &v)| v > 0)
+                .map(|(p, _)| Point2 { x: p.x - cx, y: p.y - cy })
+                .collect()
+            },
+        }
+    }
+}
+
+impl Grid
+{
+    /// Whether `p` is set once the boundary is applied, the same way the automaton counts neighbours
+    pub(crate) fn alive_through_boundary(&self, p: Point2<isize>) -> bool
+    {
+        match self.dim.resolve(p)
+        {
+            Some(p) => *self.index(p).unwrap() > 0,
+            None => self.boundary == Boundary::Alive,
+        }
+    }
+
+    /// a new grid with each cell set to `f` of the cell's point
+    fn morph(&self, f: impl Fn(Point2<isize>) -> bool) -> Grid
+    {
+        let array = self.points().map(|p| f(p) as u8).collect();
+        Grid::from_vec(self.width, self.height, array).with_boundary(self.boundary)
+    }
+
+    /// Keep only the walls where every point of `element` placed over them lands on a wall
+    pub fn erode(&self, element: &StructuringElement) -> Grid
+    {
+        let offsets = element.offsets();
+        self.morph(|p| offsets.iter().all(|o| self.alive_through_boundary([p.x + o.x, p.y + o.y].into())))
+    }
+
+    /// Set every cell that `element` reaches from some wall
+    pub fn dilate(&self, element: &StructuringElement) -> Grid
+    {
+        let offsets = element.offsets();
+        self.morph(|p| offsets.iter().any(|o| self.alive_through_boundary([p.x - o.x, p.y - o.y].into())))
+    }
+
+    /// Erode then dilate, removing walls too thin to hold `element`
+    pub fn open(&self, element: &StructuringElement) -> Grid
+    {
+        self.erode(element).dilate(element)
+    }
+
+    /// Dilate then erode, filling gaps too thin to hold `element`
+    pub fn close(&self, element: &StructuringElement) -> Grid
+    {
+        self.dilate(element).erode(element)
+    }
+
+    /// Set the cells where every point of `hits` lands on a wall and every point of `misses` lands on an open cell
+    pub fn hit_or_miss(&self, hits: &StructuringElement, misses: &StructuringElement) -> Grid
+    {
+        let hits = hits.offsets();
+        let misses = misses.offsets();
+
+        self.morph(|p|
+        {
+            hits.iter().all(|o| self.alive_through_boundary([p.x + o.x, p.y + o.y].into())) &&
+            !misses.iter().any(|o| self.alive_through_boundary([p.x + o.x, p.y + o.y].into()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::tests::soup;
+
+    fn elements() -> Vec<StructuringElement>
+    {
+        // lopsided, so a mix-up between the mask and its reflection shows
+        let mask: Grid = "OO.\n.O.\n..O\n.O.".parse().unwrap();
+        vec![
+            StructuringElement::Cross(1),
+            StructuringElement::Cross(2),
+            StructuringElement::Square(1),
+            StructuringElement::Square(2),
+            StructuringElement::Custom(mask),
+        ]
+    }
+
+    /// whether `(dx, dy)` from the centre is part of `element`, read straight off its definition
+    fn covers(element: &StructuringElement, dx: isize, dy: isize) -> bool
+    {
+        match element
+        {
+            StructuringElement::Cross(r) => (dx == 0 && dy.abs() <= *r) || (dy == 0 && dx.abs() <= *r),
+            StructuringElement::Square(r) => dx.abs() <= *r && dy.abs() <= *r,
+            StructuringElement::Custom(mask) => mask.index([dx + mask.width / 2, dy + mask.height / 2]).is_some_and(|&v| v > 0),
+        }
+    }
+
+    /// a wall past the edge under `boundary`, found without `resolve`
+    fn wall(grid: &Grid, x: isize, y: isize) -> bool
+    {
+        match grid.boundary
+        {
+            Boundary::Wrap => *grid.index([x.rem_euclid(grid.width), y.rem_euclid(grid.height)]).unwrap() > 0,
+            _ => grid.index([x, y]).is_some_and(|&v| v > 0),
+        }
+    }
+
+    #[test]
+    fn erode_and_dilate_match_a_window_scan()
+    {
+        for boundary in [Boundary::Dead, Boundary::Wrap]
+        {
+            let grid = soup(15, 15, 15, 8).with_boundary(boundary);
+            for element in elements()
+            {
+                let (eroded, dilated) = (grid.erode(&element), grid.dilate(&element));
+
+                for p in grid.points()
+                {
+                    let mut all = true;
+                    for dy in -3..=3
+                    {
+                        for dx in -3..=3
+                        {
+                            if covers(&element, dx, dy)
+                            {
+                                all &= wall(&grid, p.x + dx, p.y + dy);
+                            }
+                        }
+                    }
+                    assert_eq!(*eroded.index(p).unwrap() > 0, all, "erode {:?} {:?} at {:?}", boundary, element, p);
+                }
+
+                // spread every wall over the element instead
+                let mut spread = Grid::new(15, 15);
+                for q in grid.points().filter(|&q| *grid.index(q).unwrap() > 0)
+                {
+                    for dy in -3..=3
+                    {
+                        for dx in -3..=3
+                        {
+                            if !covers(&element, dx, dy) { continue }
+
+                            let (x, y) = (q.x + dx, q.y + dy);
+                            let target = match boundary
+                            {
+                                Boundary::Wrap => Some(Point2 { x: x.rem_euclid(15), y: y.rem_euclid(15) }),
+                                _ => spread.dim().contains([x, y]).then_some(Point2 { x, y }),
+                            };
+                            if let Some(t) = target
+                            {
+                                *spread.index_mut(t).unwrap() = 1;
+                            }
+                        }
+                    }
+                }
+                assert_eq!(dilated.as_slice(), spread.as_slice(), "dilate {:?} {:?}", boundary, element);
+            }
+        }
+    }
+
+    #[test]
+    fn open_and_close_are_idempotent()
+    {
+        let grid = soup(20, 20, 20, 9);
+        for element in elements()
+        {
+            let opened = grid.open(&element);
+            assert_eq!(opened.open(&element).as_slice(), opened.as_slice(), "{:?}", element);
+
+            let closed = grid.close(&element);
+            assert_eq!(closed.close(&element).as_slice(), closed.as_slice(), "{:?}", element);
+        }
+    }
+
+    #[test]
+    fn hit_or_miss_finds_isolated_walls()
+    {
+        let grid: Grid = "O...\n..O.\n..O.\n....".parse().unwrap();
+        let found = grid.hit_or_miss(&StructuringElement::Square(0), &StructuringElement::Custom(".O.\nO.O\n.O.".parse().unwrap()));
+        assert_eq!(found.as_slice(), [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,502 @@
+use std::io::{Read, Write};
+
+use crate::Grid;
+
+/// Which flavour of a netpbm format to write
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NetpbmEncoding
+{
+    /// Human readable samples, `P1`, `P2` and `P3`
+    Ascii,
+    /// Packed binary samples, `P4`, `P5` and `P6`
+    #[default]
+    Raw,
+}
+
+/// Palette for `write_ppm` drawing open cells white and walls black
+pub const WALL_PALETTE: [[u8; 3]; 2] = [[255, 255, 255], [0, 0, 0]];
+
+/// Palette for `write_ppm` drawing a Generations rule with `states` states:
+/// dead cells white, live cells black and the decay states fading from red to yellow
+pub fn decay_palette(states: u8) -> Vec<[u8; 3]>
+{
+    let decaying = states.saturating_sub(2) as usize;
+    let fade = (0..decaying).map(|i|
+    {
+        let green = if decaying > 1 { i * 255 / (decaying - 1) } else { 0 };
+        [255, green as u8, 0]
+    });
+
+    WALL_PALETTE.into_iter().chain(fade).collect()
+}
+
+#[derive(Debug)]
+pub enum NetpbmError
+{
+    Io(std::io::Error),
+    /// The file doesn't start with the magic number of the format being read
+    BadMagic(String),
+    /// A header field is missing or isn't a valid number
+    BadHeader(String),
+    /// The pixel data is truncated or holds a sample outside the header's range
+    BadData(String),
+    /// A cell value has no entry in the palette it is being written with
+    MissingColor(u8),
+    /// A pixel's colour isn't in the palette it is being read with
+    UnknownColor([u8; 3]),
+}
+
+impl std::fmt::Display for NetpbmError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            NetpbmError::Io(e) => write!(f, "io error: {}", e),
+            NetpbmError::BadMagic(msg) => write!(f, "bad magic number: {}", msg),
+            NetpbmError::BadHeader(msg) => write!(f, "malformed header: {}", msg),
+            NetpbmError::BadData(msg) => write!(f, "malformed pixel data: {}", msg),
+            NetpbmError::MissingColor(v) => write!(f, "no palette entry for cell value {}", v),
+            NetpbmError::UnknownColor(c) => write!(f, "colour {:?} is not in the palette", c),
+        }
+    }
+}
+
+impl std::error::Error for NetpbmError {}
+
+impl From<std::io::Error> for NetpbmError
+{
+    fn from(e: std::io::Error) -> Self
+    {
+        NetpbmError::Io(e)
+    }
+}
+
+/// The fields common to every netpbm header
+struct Header
+{
+    /// the digit after the `P`
+    kind: u8,
+    width: isize,
+    height: isize,
+    maxval: u32,
+}
+
+/// Reads a netpbm file that has been loaded into memory
+struct Parser<'a>
+{
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a>
+{
+    fn skip_whitespace_and_comments(&mut self)
+    {
+        while let Some(&b) = self.bytes.get(self.pos)
+        {
+            if b == b'#'
+            {
+                while self.bytes.get(self.pos).is_some_and(|&b| b != b'\n')
+                {
+                    self.pos += 1;
+                }
+            }
+            else if b.is_ascii_whitespace()
+            {
+                self.pos += 1;
+            }
+            else
+            {
+                break;
+            }
+        }
+    }
+
+    /// A decimal number, after any whitespace and comments
+    fn number(&mut self) -> Option<u32>
+    {
+        self.skip_whitespace_and_comments();
+
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_digit())
+        {
+            self.pos += 1;
+        }
+
+        std::str::from_utf8(&self.bytes[start..self.pos]).ok()?.parse().ok()
+    }
+
+    fn header_field(&mut self, field: &str) -> Result<u32, NetpbmError>
+    {
+        self.number()
+        .ok_or_else(||
+        {
+            match self.bytes.get(self.pos)
+            {
+                Some(&b) => NetpbmError::BadHeader(format!("expected {}, found {:?}", field, b as char)),
+                None => NetpbmError::BadHeader(format!("file ends before {}", field)),
+            }
+        })
+    }
+
+    /// Read the header, accepting any of the `kinds` of magic number
+    fn header(&mut self, kinds: &[u8]) -> Result<Header, NetpbmError>
+    {
+        let expected = || kinds.iter().map(|k| format!("P{}", k)).collect::<Vec<_>>().join(" or ");
+
+        let kind = match self.bytes.get(..2)
+        {
+            Some([b'P', k]) if kinds.contains(&(k.wrapping_sub(b'0'))) => k - b'0',
+            Some(found) => return Err(NetpbmError::BadMagic(format!("expected {}, found {:?}", expected(), String::from_utf8_lossy(found)))),
+            None => return Err(NetpbmError::BadMagic(format!("expected {}, found end of file", expected()))),
+        };
+        self.pos = 2;
+
+        let width = self.header_field("width")?;
+        let height = self.header_field("height")?;
+        if width == 0 || height == 0
+        {
+            return Err(NetpbmError::BadHeader(format!("image is {}x{}, both sides must be positive", width, height)));
+        }
+
+        let maxval = match kind
+        {
+            1 | 4 => 1,
+            _ => self.header_field("maxval")?,
+        };
+        if maxval == 0 || maxval > 255
+        {
+            return Err(NetpbmError::BadHeader(format!("maxval {} is outside 1..=255", maxval)));
+        }
+
+        // binary rasters start after exactly one whitespace byte
+        if kind >= 4
+        {
+            match self.bytes.get(self.pos)
+            {
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                _ => return Err(NetpbmError::BadHeader(String::from("expected a single whitespace byte before the raster"))),
+            }
+        }
+
+        // every sample takes at least a byte (P4 packs 8 to a byte), so a raster that can't fit
+        // in what's left of the file is caught here, before the grid is allocated
+        let (w, h) = (width as usize, height as usize);
+        let needed = match kind
+        {
+            4 => w.div_ceil(8).checked_mul(h),
+            3 | 6 => w.checked_mul(h).and_then(|n| n.checked_mul(3)),
+            _ => w.checked_mul(h),
+        };
+        match needed
+        {
+            Some(n) if n <= isize::MAX as usize && n <= self.bytes.len() - self.pos => (),
+            _ => return Err(NetpbmError::BadHeader(format!("a {}x{} image doesn't fit in the file", width, height))),
+        }
+
+        Ok(Header { kind, width: width as isize, height: height as isize, maxval })
+    }
+
+    fn ascii_sample(&mut self, maxval: u32) -> Result<u8, NetpbmError>
+    {
+        let sample = self.number().ok_or_else(|| NetpbmError::BadData(format!("expected a sample at byte {}", self.pos)))?;
+        if sample > maxval
+        {
+            return Err(NetpbmError::BadData(format!("sample {} is larger than maxval {}", sample, maxval)));
+        }
+
+        Ok(sample as u8)
+    }
+
+    /// a `P1` bit, which doesn't need whitespace between samples
+    fn ascii_bit(&mut self) -> Result<u8, NetpbmError>
+    {
+        self.skip_whitespace_and_comments();
+        let bit = match self.bytes.get(self.pos)
+        {
+            Some(b'0') => 0,
+            Some(b'1') => 1,
+            Some(&b) => return Err(NetpbmError::BadData(format!("expected 0 or 1, found {:?}", b as char))),
+            None => return Err(NetpbmError::BadData(String::from("file ends inside the raster"))),
+        };
+        self.pos += 1;
+
+        Ok(bit)
+    }
+
+    fn raw_bytes(&mut self, n: usize) -> Result<&'a [u8], NetpbmError>
+    {
+        let bytes = self.bytes
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| NetpbmError::BadData(String::from("file ends inside the raster")))?;
+        self.pos += n;
+
+        Ok(bytes)
+    }
+
+    fn raw_sample(&mut self, maxval: u32) -> Result<u8, NetpbmError>
+    {
+        let sample = self.raw_bytes(1)?[0];
+        if sample as u32 > maxval
+        {
+            return Err(NetpbmError::BadData(format!("sample {} is larger than maxval {}", sample, maxval)));
+        }
+
+        Ok(sample)
+    }
+}
+
+fn read_all(mut r: impl Read) -> Result<Vec<u8>, NetpbmError>
+{
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+impl Grid
+{
+    /// Write walls (non-zero cells) as black pixels and open cells as white
+    pub fn write_pbm(&self, mut w: impl Write, encoding: NetpbmEncoding) -> Result<(), NetpbmError>
+    {
+        let wall = |x, y| *self.index([x, y]).unwrap() > 0;
+
+        match encoding
+        {
+            NetpbmEncoding::Ascii =>
+            {
+                writeln!(w, "P1\n{} {}", self.width, self.height)?;
+                for y in 0..self.height
+                {
+                    let row: Vec<&str> = (0..self.width).map(|x| if wall(x, y) { "1" } else { "0" }).collect();
+                    writeln!(w, "{}", row.join(" "))?;
+                }
+            },
+            NetpbmEncoding::Raw =>
+            {
+                writeln!(w, "P4\n{} {}", self.width, self.height)?;
+                for y in 0..self.height
+                {
+                    // rows are packed most significant bit first and padded to a whole byte
+                    let mut row = vec![0u8; (self.width as usize).div_ceil(8)];
+                    for x in 0..self.width
+                    {
+                        if wall(x, y)
+                        {
+                            row[x as usize / 8] |= 0x80 >> (x % 8);
+                        }
+                    }
+                    w.write_all(&row)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Read a `P1` or `P4` bitmap, with black pixels as walls (1) and white as open (0)
+    pub fn read_pbm(r: impl Read) -> Result<Grid, NetpbmError>
+    {
+        let bytes = read_all(r)?;
+        let mut parser = Parser { bytes: &bytes, pos: 0 };
+        let header = parser.header(&[1, 4])?;
+
+        let mut grid = Grid::new(header.width, header.height);
+        let row_bytes = (header.width as usize).div_ceil(8);
+        for y in 0..header.height
+        {
+            if header.kind == 1
+            {
+                for x in 0..header.width
+                {
+                    *grid.index_mut([x, y]).unwrap() = parser.ascii_bit()?;
+                }
+            }
+            else
+            {
+                let row = parser.raw_bytes(row_bytes)?;
+                for x in 0..header.width
+                {
+                    *grid.index_mut([x, y]).unwrap() = (row[x as usize / 8] << (x % 8)) >> 7;
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+
+    /// Write every cell value as a grey level, with the largest value in the grid as maxval
+    pub fn write_pgm(&self, mut w: impl Write, encoding: NetpbmEncoding) -> Result<(), NetpbmError>
+    {
+        let maxval = self.array.iter().copied().max().unwrap_or(0).max(1);
+
+        match encoding
+        {
+            NetpbmEncoding::Ascii =>
+            {
+                writeln!(w, "P2\n{} {}\n{}", self.width, self.height, maxval)?;
+                for y in 0..self.height
+                {
+                    let row: Vec<String> = (0..self.width).map(|x| self.index([x, y]).unwrap().to_string()).collect();
+                    writeln!(w, "{}", row.join(" "))?;
+                }
+            },
+            NetpbmEncoding::Raw =>
+            {
+                writeln!(w, "P5\n{} {}\n{}", self.width, self.height, maxval)?;
+                w.write_all(&self.array)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Read a `P2` or `P5` greymap, keeping each sample as the cell value
+    pub fn read_pgm(r: impl Read) -> Result<Grid, NetpbmError>
+    {
+        let bytes = read_all(r)?;
+        let mut parser = Parser { bytes: &bytes, pos: 0 };
+        let header = parser.header(&[2, 5])?;
+
+        let mut grid = Grid::new(header.width, header.height);
+        for v in grid.array.iter_mut()
+        {
+            *v = match header.kind
+            {
+                2 => parser.ascii_sample(header.maxval)?,
+                _ => parser.raw_sample(header.maxval)?,
+            };
+        }
+
+        Ok(grid)
+    }
+
+    /// Write each cell as `palette[value]`
+    pub fn write_ppm(&self, mut w: impl Write, encoding: NetpbmEncoding, palette: &[[u8; 3]]) -> Result<(), NetpbmError>
+    {
+        let color = |v: u8| palette.get(v as usize).ok_or(NetpbmError::MissingColor(v));
+
+        match encoding
+        {
+            NetpbmEncoding::Ascii =>
+            {
+                writeln!(w, "P3\n{} {}\n255", self.width, self.height)?;
+                for y in 0..self.height
+                {
+                    let mut row = Vec::new();
+                    for x in 0..self.width
+                    {
+                        let [r, g, b] = color(*self.index([x, y]).unwrap())?;
+                        row.push(format!("{} {} {}", r, g, b));
+                    }
+                    writeln!(w, "{}", row.join("  "))?;
+                }
+            },
+            NetpbmEncoding::Raw =>
+            {
+                writeln!(w, "P6\n{} {}\n255", self.width, self.height)?;
+                for &v in &self.array
+                {
+                    w.write_all(color(v)?)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Read a `P3` or `P6` pixmap, turning every pixel back into its index in `palette`
+    pub fn read_ppm(r: impl Read, palette: &[[u8; 3]]) -> Result<Grid, NetpbmError>
+    {
+        let bytes = read_all(r)?;
+        let mut parser = Parser { bytes: &bytes, pos: 0 };
+        let header = parser.header(&[3, 6])?;
+
+        // palettes are matched at full range, so rescale files saved with a smaller maxval
+        let scale = |s: u8| (s as u32 * 255 / header.maxval) as u8;
+
+        let mut grid = Grid::new(header.width, header.height);
+        for v in grid.array.iter_mut()
+        {
+            let mut rgb = [0; 3];
+            for channel in rgb.iter_mut()
+            {
+                *channel = scale(match header.kind
+                {
+                    3 => parser.ascii_sample(header.maxval)?,
+                    _ => parser.raw_sample(header.maxval)?,
+                });
+            }
+
+            *v = palette
+                .iter()
+                .position(|&c| c == rgb)
+                .ok_or(NetpbmError::UnknownColor(rgb))? as u8;
+        }
+
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn maze() -> Grid
+    {
+        "#####\n#...#\n#.#.#\n#.#..\n###.#".parse().unwrap()
+    }
+
+    #[test]
+    fn pbm_round_trips()
+    {
+        for encoding in [NetpbmEncoding::Ascii, NetpbmEncoding::Raw]
+        {
+            let mut bytes = Vec::new();
+            maze().write_pbm(&mut bytes, encoding).unwrap();
+            assert_eq!(Grid::read_pbm(&bytes[..]).unwrap().as_slice(), maze().as_slice());
+        }
+    }
+
+    #[test]
+    fn pgm_and_ppm_round_trip()
+    {
+        let mut grid = maze();
+        *grid.index_mut([2, 2]).unwrap() = 3;
+        let palette = crate::decay_palette(4);
+
+        for encoding in [NetpbmEncoding::Ascii, NetpbmEncoding::Raw]
+        {
+            let mut bytes = Vec::new();
+            grid.write_pgm(&mut bytes, encoding).unwrap();
+            assert_eq!(Grid::read_pgm(&bytes[..]).unwrap().as_slice(), grid.as_slice());
+
+            let mut bytes = Vec::new();
+            grid.write_ppm(&mut bytes, encoding, &palette).unwrap();
+            assert_eq!(Grid::read_ppm(&bytes[..], &palette).unwrap().as_slice(), grid.as_slice());
+        }
+    }
+
+    #[test]
+    fn reads_comments_and_tight_bits()
+    {
+        let grid = Grid::read_pbm(&b"P1\n# a comment\n3 2\n010\n# another\n1 1 0"[..]).unwrap();
+        assert_eq!(grid.as_slice(), &[0, 1, 0, 1, 1, 0]);
+    }
+
+    #[test]
+    fn rejects_malformed_files()
+    {
+        let header = |bytes: &[u8]| matches!(Grid::read_pgm(bytes), Err(NetpbmError::BadHeader(_)));
+
+        assert!(matches!(Grid::read_pbm(&b"P7\n1 1\n"[..]), Err(NetpbmError::BadMagic(_))));
+        assert!(matches!(Grid::read_pbm(&b"P4\n4294967295 4294967295\n"[..]), Err(NetpbmError::BadHeader(_))));
+        assert!(header(b"P5 100000 100000 255\n"));
+        assert!(header(b"P5 0 3 255\n"));
+        assert!(header(b"P5 2 2 256\n"));
+        assert!(header(b"P2 2 x"));
+        assert!(matches!(Grid::read_pgm(&b"P2 2 1 3\n1 4"[..]), Err(NetpbmError::BadData(_))));
+        assert!(matches!(Grid::read_ppm(&b"P3 1 1 255\n1 2 3"[..], &WALL_PALETTE), Err(NetpbmError::UnknownColor(_))));
+    }
+}
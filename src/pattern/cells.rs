@@ -0,0 +1,213 @@
+use std::io::{Read, Write};
+
+use crate::Grid;
+
+use super::{Pattern, PatternError};
+
+/// `.` is dead, `O` is alive, and `*` or `#` are taken as alive too
+fn cell(c: char, row: usize) -> Result<u8, PatternError>
+{
+    match c
+    {
+        '.' => Ok(0),
+        'O' | '*' | '#' => Ok(1),
+        c => Err(PatternError::BadData(format!("unexpected {:?} in row {}", c, row))),
+    }
+}
+
+/// Pack rows of ascii art into a grid, padding short rows with dead cells
+fn rows_to_grid(rows: &[&str]) -> Result<Grid, PatternError>
+{
+    let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+    if width == 0
+    {
+        return Err(PatternError::BadData(String::from("pattern has no cells")));
+    }
+
+    let mut grid = Grid::new(width as isize, rows.len() as isize);
+    for (y, row) in rows.iter().enumerate()
+    {
+        for (x, c) in row.chars().enumerate()
+        {
+            *grid.index_mut([x as isize, y as isize]).unwrap() = cell(c, y)?;
+        }
+    }
+
+    Ok(grid)
+}
+
+impl Pattern
+{
+    /// Read a plaintext `.cells` pattern, with `!Name:` and `!Author:` lines
+    /// and any other `!` lines kept as comments
+    pub fn read_cells(mut r: impl Read) -> Result<Pattern, PatternError>
+    {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        let mut pattern = Pattern::from(Grid::new(1, 1));
+        let mut rows = Vec::new();
+        for line in text.lines().map(str::trim_end)
+        {
+            if let Some(comment) = line.strip_prefix('!')
+            {
+                if let Some(name) = comment.strip_prefix("Name:")
+                {
+                    pattern.name = Some(name.trim().to_string());
+                }
+                else if let Some(author) = comment.strip_prefix("Author:")
+                {
+                    pattern.author = Some(author.trim().to_string());
+                }
+                else
+                {
+                    pattern.comments.push(comment.trim().to_string());
+                }
+            }
+            else
+            {
+                rows.push(line);
+            }
+        }
+
+        // a blank line is an empty row, unless it's trailing
+        while rows.last().is_some_and(|r| r.is_empty())
+        {
+            rows.pop();
+        }
+
+        pattern.grid = rows_to_grid(&rows)?;
+        Ok(pattern)
+    }
+
+    /// Write the pattern as `.cells` plaintext, drawing every cell above 0 as `O`
+    pub fn write_cells(&self, mut w: impl Write) -> Result<(), PatternError>
+    {
+        if let Some(name) = &self.name
+        {
+            writeln!(w, "!Name: {}", name)?;
+        }
+        if let Some(author) = &self.author
+        {
+            writeln!(w, "!Author: {}", author)?;
+        }
+        for comment in &self.comments
+        {
+            writeln!(w, "!{}", comment)?;
+        }
+
+        for y in 0..self.grid.height
+        {
+            let row: String = (0..self.grid.width)
+                .map(|x| if *self.grid.index([x, y]).unwrap() > 0 { 'O' } else { '.' })
+                .collect();
+            writeln!(w, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Grid
+{
+    /// Read just the cells of a `.cells` pattern, see [`Pattern::read_cells`] for the metadata
+    pub fn read_cells(r: impl Read) -> Result<Grid, PatternError>
+    {
+        Ok(Pattern::read_cells(r)?.grid)
+    }
+
+    pub fn write_cells(&self, w: impl Write) -> Result<(), PatternError>
+    {
+        Pattern::from(self.clone()).write_cells(w)
+    }
+}
+
+/// Parse ascii art where `.` is open and `O` or `#` is a wall, so mazes can be written inline.
+/// Surrounding whitespace and blank lines are skipped, and every row must be the same width.
+///
+/// ```
+/// use mazes::Grid;
+///
+/// let maze: Grid = "#####\n#...#\n###.#".parse().unwrap();
+///
+/// assert!(maze.find_path_with_a_star([1, 1].into(), [3, 2].into()).is_some());
+/// ```
+impl std::str::FromStr for Grid
+{
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let rows: Vec<&str> = s.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+
+        if let Some((y, row)) = rows.iter().enumerate().find(|(_, r)| r.chars().count() != rows[0].chars().count())
+        {
+            return Err(PatternError::BadData(format!("row {} is {} cells wide, the first row is {}", y, row.chars().count(), rows[0].chars().count())));
+        }
+
+        rows_to_grid(&rows)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::tests::soup;
+
+    fn round_trip(pattern: &Pattern) -> Pattern
+    {
+        let mut bytes = Vec::new();
+        pattern.write_cells(&mut bytes).unwrap();
+        Pattern::read_cells(&bytes[..]).unwrap()
+    }
+
+    #[test]
+    fn random_grids_round_trip()
+    {
+        for seed in 0..4
+        {
+            let pattern = Pattern::from(soup(30, 20, 18, seed));
+            let back = round_trip(&pattern);
+
+            assert_eq!((back.grid.width, back.grid.height), (30, 20));
+            assert_eq!(back.grid.as_slice(), pattern.grid.as_slice());
+        }
+    }
+
+    #[test]
+    fn metadata_is_kept()
+    {
+        let text = "!Name: Glider\n!Author: Richard K. Guy\n!The smallest spaceship\n.O.\n..O\nOOO\n";
+        let pattern = Pattern::read_cells(text.as_bytes()).unwrap();
+
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(pattern.comments, ["The smallest spaceship"]);
+
+        let back = round_trip(&pattern);
+        assert_eq!(back.name, pattern.name);
+        assert_eq!(back.author, pattern.author);
+        assert_eq!(back.comments, pattern.comments);
+        assert_eq!(back.grid.as_slice(), pattern.grid.as_slice());
+    }
+
+    #[test]
+    fn ragged_rows_are_padded_with_dead_cells()
+    {
+        let grid = Grid::read_cells("O\n\n..*O\n.O\n\n".as_bytes()).unwrap();
+
+        assert_eq!((grid.width, grid.height), (4, 4));
+        assert_eq!(grid.as_slice(), [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn unknown_cells_are_rejected()
+    {
+        for text in ["..x\n", "O.O\n.b.\n", "O 0\n"]
+        {
+            assert!(matches!(Grid::read_cells(text.as_bytes()), Err(PatternError::BadData(_))), "{:?}", text);
+        }
+        assert!(matches!(Grid::read_cells("!Name: empty\n".as_bytes()), Err(PatternError::BadData(_))));
+    }
+}
@@ -0,0 +1,180 @@
+use std::io::{Read, Write};
+
+use crate::Grid;
+
+use super::{Pattern, PatternError};
+
+const HEADER: &str = "#Life 1.06";
+
+/// The most cells a pattern's bounding box may cover, since far apart coordinates would otherwise
+/// make a huge mostly empty grid
+pub const LIFE106_MAX_CELLS: isize = 1 << 26;
+
+impl Pattern
+{
+    /// Read a Life 1.06 list of live `x y` coordinates.
+    ///
+    /// The grid is the bounding box of the live cells, so any empty margin the pattern was saved with is lost,
+    /// and a file without live cells reads as a single dead cell. Boxes over [`LIFE106_MAX_CELLS`] are rejected.
+    /// `#N`, `#O` and `#D` lines are read as the name, author and comments.
+    pub fn read_life106(mut r: impl Read) -> Result<Pattern, PatternError>
+    {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        match lines.next()
+        {
+            Some(HEADER) => (),
+            Some(found) => return Err(PatternError::BadHeader(format!("expected {:?}, found {:?}", HEADER, found))),
+            None => return Err(PatternError::BadHeader(format!("expected {:?}, found end of file", HEADER))),
+        }
+
+        let mut pattern = Pattern::from(Grid::new(1, 1));
+        let mut points = Vec::new();
+        for line in lines
+        {
+            if let Some(meta) = line.strip_prefix('#')
+            {
+                let (tag, value) = meta.split_at(meta.len().min(1));
+                let value = value.trim().to_string();
+                match tag
+                {
+                    "N" => pattern.name = Some(value),
+                    "O" => pattern.author = Some(value),
+                    "D" | "C" => pattern.comments.push(value),
+                    _ => (),
+                }
+                continue;
+            }
+
+            let coords: Vec<isize> = line
+                .split_whitespace()
+                .map(|n| n.parse::<isize>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| PatternError::BadData(format!("expected x y, found {:?}", line)))?;
+
+            match coords[..]
+            {
+                [x, y] => points.push((x, y)),
+                _ => return Err(PatternError::BadData(format!("expected x y, found {:?}", line))),
+            }
+        }
+
+        let (Some(min_x), Some(min_y)) = (points.iter().map(|p| p.0).min(), points.iter().map(|p| p.1).min())
+        else
+        {
+            return Ok(pattern);
+        };
+        let max_x = points.iter().map(|p| p.0).max().unwrap();
+        let max_y = points.iter().map(|p| p.1).max().unwrap();
+
+        let span = |min: isize, max: isize| max.checked_sub(min)?.checked_add(1);
+        let (width, height) = match (span(min_x, max_x), span(min_y, max_y))
+        {
+            (Some(w), Some(h)) if w.checked_mul(h).is_some_and(|area| area <= LIFE106_MAX_CELLS) => (w, h),
+            _ => return Err(PatternError::BadData(format!(
+                "live cells span ({}, {}) to ({}, {}), more than {} cells", min_x, min_y, max_x, max_y, LIFE106_MAX_CELLS))),
+        };
+
+        pattern.grid = Grid::new(width, height);
+        for (x, y) in points
+        {
+            *pattern.grid.index_mut([x - min_x, y - min_y]).unwrap() = 1;
+        }
+
+        Ok(pattern)
+    }
+
+    /// Write every cell above 0 as an `x y` line, relative to the grid's top-left corner
+    pub fn write_life106(&self, mut w: impl Write) -> Result<(), PatternError>
+    {
+        writeln!(w, "{}", HEADER)?;
+        if let Some(name) = &self.name
+        {
+            writeln!(w, "#N {}", name)?;
+        }
+        if let Some(author) = &self.author
+        {
+            writeln!(w, "#O {}", author)?;
+        }
+        for comment in &self.comments
+        {
+            writeln!(w, "#D {}", comment)?;
+        }
+
+        for y in 0..self.grid.height
+        {
+            for x in 0..self.grid.width
+            {
+                if *self.grid.index([x, y]).unwrap() > 0
+                {
+                    writeln!(w, "{} {}", x, y)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Grid
+{
+    /// Read just the cells of a Life 1.06 file, see [`Pattern::read_life106`] for the metadata
+    pub fn read_life106(r: impl Read) -> Result<Grid, PatternError>
+    {
+        Ok(Pattern::read_life106(r)?.grid)
+    }
+
+    pub fn write_life106(&self, w: impl Write) -> Result<(), PatternError>
+    {
+        Pattern::from(self.clone()).write_life106(w)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn round_trips_with_metadata()
+    {
+        let mut pattern = Pattern::from("..O\nO.O\n.OO".parse::<Grid>().unwrap());
+        pattern.name = Some(String::from("Glider"));
+        pattern.comments.push(String::from("moves diagonally"));
+
+        let mut bytes = Vec::new();
+        pattern.write_life106(&mut bytes).unwrap();
+        let back = Pattern::read_life106(&bytes[..]).unwrap();
+
+        assert_eq!(back.grid.as_slice(), pattern.grid.as_slice());
+        assert_eq!(back.name, pattern.name);
+        assert_eq!(back.comments, pattern.comments);
+    }
+
+    #[test]
+    fn empty_pattern_reads_back_as_one_dead_cell()
+    {
+        let mut bytes = Vec::new();
+        Grid::new(4, 3).write_life106(&mut bytes).unwrap();
+        assert_eq!(Grid::read_life106(&bytes[..]).unwrap().as_slice(), &[0]);
+    }
+
+    #[test]
+    fn rejects_bad_lines_and_huge_boxes()
+    {
+        let bad: [&[u8]; 5] = [
+            b"#Life 1.05\n0 0\n",
+            b"#Life 1.06\n0\n",
+            b"#Life 1.06\n0 x\n",
+            b"#Life 1.06\n0 0\n9223372036854775807 0\n",
+            b"#Life 1.06\n0 0\n100000 100000\n",
+        ];
+
+        for bytes in bad
+        {
+            assert!(Grid::read_life106(bytes).is_err(), "{:?}", String::from_utf8_lossy(bytes));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+mod cells;
+mod life106;
+mod rle;
+
+pub use life106::LIFE106_MAX_CELLS;
+pub use rle::RLE_MAX_CELLS;
+
+use crate::{Automaton, DynamicRules, Grid};
+
+/// A grid loaded from or saved to a Life pattern file, along with the file's metadata
+#[derive(Clone, Debug)]
+pub struct Pattern
+{
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    /// The rule the pattern was made for, if the file names one
+    pub rules: Option<DynamicRules>,
+    pub grid: Grid,
+}
+
+#[derive(Debug)]
+pub enum PatternError
+{
+    Io(std::io::Error),
+    /// The size line or another header field is missing or malformed
+    BadHeader(String),
+    /// The cells themselves can't be read, or don't fit in the declared size
+    BadData(String),
+    /// The rule field isn't something `DynamicRules` understands
+    BadRule(String),
+}
+
+impl std::fmt::Display for PatternError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            PatternError::Io(e) => write!(f, "io error: {}", e),
+            PatternError::BadHeader(msg) => write!(f, "malformed header: {}", msg),
+            PatternError::BadData(msg) => write!(f, "malformed pattern: {}", msg),
+            PatternError::BadRule(msg) => write!(f, "unsupported rule: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+impl From<std::io::Error> for PatternError
+{
+    fn from(e: std::io::Error) -> Self
+    {
+        PatternError::Io(e)
+    }
+}
+
+impl From<Grid> for Pattern
+{
+    fn from(grid: Grid) -> Self
+    {
+        Pattern
+        {
+            name: None,
+            author: None,
+            comments: Vec::new(),
+            rules: None,
+            grid,
+        }
+    }
+}
+
+impl Pattern
+{
+    pub fn with_rules(mut self, rules: DynamicRules) -> Self
+    {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// Start an automaton on the pattern, running its own rule or `B3/S23` if it doesn't name one
+    pub fn into_automaton(self) -> Automaton<DynamicRules>
+    {
+        let rules = self.rules.unwrap_or_else(|| DynamicRules::new(&[3], &[2, 3]));
+        Automaton::new(self.grid, rules)
+    }
+}
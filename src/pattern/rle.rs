@@ -0,0 +1,395 @@
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use crate::{Boundary, DynamicRules, Grid};
+
+use super::{Pattern, PatternError};
+
+/// Golly keeps lines of the body under 70 characters
+const MAX_LINE: usize = 70;
+
+/// The most cells the `x = .., y = ..` line may ask for, since the grid is made before any run is read
+pub const RLE_MAX_CELLS: isize = 1 << 26;
+
+/// Read a `rule = ..` field, either `B3/S23` or the older `23/3` survive/birth order,
+/// with an optional Golly topology suffix such as `:T40,30`
+fn parse_rule(field: &str) -> Result<(DynamicRules, Option<Boundary>), PatternError>
+{
+    let (rule, topology) = match field.split_once(':')
+    {
+        Some((rule, topology)) => (rule.trim(), Some(topology.trim())),
+        None => (field.trim(), None),
+    };
+
+    let rules = DynamicRules::from_str(rule).map_err(PatternError::BadRule)?;
+
+    let boundary = match topology.map(|t| t.chars().next())
+    {
+        None => None,
+        Some(Some('P')) => Some(Boundary::Dead),
+        Some(Some('T')) => Some(Boundary::Wrap),
+        Some(Some('K')) => Some(Boundary::Klein),
+        Some(_) => return Err(PatternError::BadRule(format!("topology {:?} is not supported", topology.unwrap()))),
+    };
+
+    Ok((rules, boundary))
+}
+
+/// The tag for one cell state: `b`/`o` for two-state patterns,
+/// `.` and `A`..`X` with a `p`..`y` prefix past 24 otherwise
+fn state_tag(v: u8, multistate: bool) -> String
+{
+    match (v, multistate)
+    {
+        (0, false) => String::from("b"),
+        (_, false) => String::from("o"),
+        (0, true) => String::from("."),
+        (1..=24, true) => char::from(b'A' + v - 1).to_string(),
+        (_, true) =>
+        {
+            let v = v - 25;
+            format!("{}{}", char::from(b'p' + v / 24), char::from(b'A' + v % 24))
+        },
+    }
+}
+
+/// Read the `x = .., y = .., rule = ..` line
+fn parse_size_line(line: &str) -> Result<(isize, isize, Option<&str>), PatternError>
+{
+    // the rule goes last and can hold commas of its own, like `rule = B3/S23:T40,30`
+    let (sizes, rule) = match line.split_once("rule")
+    {
+        Some((sizes, rule)) =>
+        {
+            let rule = rule
+                .trim_start()
+                .strip_prefix('=')
+                .ok_or_else(|| PatternError::BadHeader(String::from("expected rule = ..")))?;
+            (sizes.trim_end().trim_end_matches(','), Some(rule.trim()))
+        },
+        None => (line, None),
+    };
+
+    let (mut x, mut y) = (None, None);
+    for field in sizes.split(',')
+    {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| PatternError::BadHeader(format!("expected key = value, found {:?}", field.trim())))?;
+
+        match key.trim()
+        {
+            "x" => x = value.trim().parse::<isize>().ok(),
+            "y" => y = value.trim().parse::<isize>().ok(),
+            key => return Err(PatternError::BadHeader(format!("unknown field {:?}", key))),
+        }
+    }
+
+    match (x, y)
+    {
+        (Some(x), Some(y)) if x > 0 && y > 0 && x.checked_mul(y).is_some_and(|area| area <= RLE_MAX_CELLS) => Ok((x, y, rule)),
+        (Some(x), Some(y)) if x > 0 && y > 0 => Err(PatternError::BadHeader(format!("pattern is {}x{}, more than {} cells", x, y, RLE_MAX_CELLS))),
+        (Some(x), Some(y)) => Err(PatternError::BadHeader(format!("pattern is {}x{}, both sides must be positive", x, y))),
+        _ => Err(PatternError::BadHeader(format!("expected x = .., y = .., found {:?}", line))),
+    }
+}
+
+impl Pattern
+{
+    /// Read a run length encoded pattern, the format used by Golly and the LifeWiki.
+    /// Sizes over [`RLE_MAX_CELLS`] are rejected.
+    ///
+    /// ```
+    /// use mazes::Pattern;
+    ///
+    /// let glider = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!";
+    /// let pattern = Pattern::read_rle(glider.as_bytes()).unwrap();
+    ///
+    /// assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    /// assert_eq!(pattern.rules.unwrap().to_string(), "B3/S23");
+    /// assert_eq!(pattern.grid.as_slice(), [0, 1, 0, 0, 0, 1, 1, 1, 1]);
+    /// ```
+    pub fn read_rle(mut r: impl Read) -> Result<Pattern, PatternError>
+    {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        let mut name = None;
+        let mut author = None;
+        let mut comments = Vec::new();
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+
+        let size_line = loop
+        {
+            match lines.next()
+            {
+                Some(l) if l.starts_with('#') =>
+                {
+                    let (tag, value) = l.split_at(l.len().min(2));
+                    let value = value.trim().to_string();
+                    match tag
+                    {
+                        "#N" => name = Some(value),
+                        "#O" => author = Some(value),
+                        "#C" | "#c" => comments.push(value),
+                        // offsets (#P, #R) and anything else are ignored
+                        _ => (),
+                    }
+                },
+                Some(l) => break l,
+                None => return Err(PatternError::BadHeader(String::from("file ends before the x = .., y = .. line"))),
+            }
+        };
+
+        let (width, height, rule) = parse_size_line(size_line)?;
+        let (rules, boundary) = match rule
+        {
+            Some(rule) =>
+            {
+                let (rules, boundary) = parse_rule(rule)?;
+                (Some(rules), boundary)
+            },
+            None => (None, None),
+        };
+
+        let mut grid = Grid::new(width, height).with_boundary(boundary.unwrap_or_default());
+        let (mut x, mut y): (isize, isize) = (0, 0);
+        let mut run: Option<isize> = None;
+        let mut prefix: Option<u8> = None;
+
+        'body: for l in lines
+        {
+            for c in l.chars()
+            {
+                let n = run.unwrap_or(1);
+
+                let state = match c
+                {
+                    '0'..='9' =>
+                    {
+                        run = run
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|r| r.checked_add(c.to_digit(10).unwrap() as isize));
+                        if run.is_none()
+                        {
+                            return Err(PatternError::BadData(format!("run at row {} is too long", y)));
+                        }
+                        continue;
+                    },
+                    '!' => break 'body,
+                    '$' =>
+                    {
+                        x = 0;
+                        // rows past the end are caught by the next run, and a trailing `$` is harmless
+                        y = y.saturating_add(n);
+                        run = None;
+                        continue;
+                    },
+                    'p'..='y' if prefix.is_none() =>
+                    {
+                        prefix = Some(c as u8 - b'p');
+                        continue;
+                    },
+                    'b' | '.' if prefix.is_none() => 0,
+                    'o' if prefix.is_none() => 1,
+                    'A'..='X' => match prefix.take()
+                    {
+                        Some(p) => 25 + p as u16 * 24 + (c as u8 - b'A') as u16,
+                        None => 1 + (c as u8 - b'A') as u16,
+                    },
+                    c if c.is_whitespace() => continue,
+                    c => return Err(PatternError::BadData(format!("unexpected {:?} at row {}", c, y))),
+                };
+
+                let state = u8::try_from(state).map_err(|_| PatternError::BadData(format!("state {} doesn't fit in a cell", state)))?;
+                if y >= height || n > width - x
+                {
+                    return Err(PatternError::BadData(format!("a run at ({}, {}) lies outside the {}x{} pattern", x, y, width, height)));
+                }
+
+                for i in x..x + n
+                {
+                    *grid.index_mut([i, y]).unwrap() = state;
+                }
+                x += n;
+                run = None;
+            }
+        }
+
+        Ok(Pattern { name, author, comments, rules, grid })
+    }
+
+    /// Write the pattern as RLE, using `b`/`o` unless some cell is above 1.
+    ///
+    /// Reading the output back gives the same pattern:
+    ///
+    /// ```
+    /// use mazes::Pattern;
+    ///
+    /// let gun = "\
+    /// #N Gosper glider gun
+    /// x = 36, y = 9, rule = B3/S23
+    /// 24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+    /// obo$10bo5bo7bo$11bo3bo$12b2o!
+    /// ";
+    /// let pattern = Pattern::read_rle(gun.as_bytes()).unwrap();
+    /// assert_eq!(pattern.grid.as_slice().iter().filter(|&&v| v > 0).count(), 36);
+    ///
+    /// let mut out = Vec::new();
+    /// pattern.write_rle(&mut out).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), gun);
+    /// ```
+    pub fn write_rle(&self, mut w: impl Write) -> Result<(), PatternError>
+    {
+        if let Some(name) = &self.name
+        {
+            writeln!(w, "#N {}", name)?;
+        }
+        if let Some(author) = &self.author
+        {
+            writeln!(w, "#O {}", author)?;
+        }
+        for comment in &self.comments
+        {
+            writeln!(w, "#C {}", comment)?;
+        }
+
+        let grid = &self.grid;
+        write!(w, "x = {}, y = {}", grid.width, grid.height)?;
+        if let Some(rules) = &self.rules
+        {
+            write!(w, ", rule = {}", rules)?;
+            match grid.boundary
+            {
+                Boundary::Wrap => write!(w, ":T{},{}", grid.width, grid.height)?,
+                Boundary::Klein => write!(w, ":K{},{}", grid.width, grid.height)?,
+                _ => (),
+            }
+        }
+        writeln!(w)?;
+
+        // Generations rules always use the multistate tags, like Golly does
+        let multistate = grid.array.iter().any(|&v| v > 1) || self.rules.as_ref().is_some_and(|r| r.states > 2);
+
+        // runs are collected as tokens first so lines can be wrapped between them
+        let mut tokens = Vec::new();
+        let mut push = |n: isize, tag: String|
+        {
+            match n
+            {
+                1 => tokens.push(tag),
+                _ => tokens.push(format!("{}{}", n, tag)),
+            }
+        };
+
+        // empty rows and trailing dead cells are left out
+        let mut cursor_y = 0;
+        for y in 0..grid.height
+        {
+            let row = &grid.array[(y * grid.width) as usize..((y + 1) * grid.width) as usize];
+            let Some(last) = row.iter().rposition(|&v| v > 0)
+            else
+            {
+                continue;
+            };
+
+            if y > cursor_y
+            {
+                push(y - cursor_y, String::from("$"));
+                cursor_y = y;
+            }
+
+            let mut x = 0;
+            while x <= last
+            {
+                let run = row[x..=last].iter().take_while(|&&v| v == row[x]).count();
+                push(run as isize, state_tag(row[x], multistate));
+                x += run;
+            }
+        }
+        tokens.push(String::from("!"));
+
+        let mut line = String::new();
+        for token in tokens
+        {
+            if line.len() + token.len() > MAX_LINE
+            {
+                writeln!(w, "{}", line)?;
+                line.clear();
+            }
+            line.push_str(&token);
+        }
+        writeln!(w, "{}", line)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::tests::soup;
+
+    fn round_trip(pattern: &Pattern) -> Pattern
+    {
+        let mut bytes = Vec::new();
+        pattern.write_rle(&mut bytes).unwrap();
+        Pattern::read_rle(&bytes[..]).unwrap()
+    }
+
+    #[test]
+    fn random_grids_round_trip()
+    {
+        for seed in 0..4
+        {
+            let pattern = Pattern::from(soup(90, 90, 80, seed)).with_rules("B36/S23".parse().unwrap());
+            let back = round_trip(&pattern);
+
+            assert_eq!(back.grid.as_slice(), pattern.grid.as_slice());
+            assert_eq!(back.rules.unwrap().to_string(), "B36/S23");
+        }
+    }
+
+    #[test]
+    fn multistate_cells_and_topology_round_trip()
+    {
+        let mut grid = Grid::new(60, 3).with_boundary(Boundary::Klein);
+        for x in 0..60
+        {
+            *grid.index_mut([x, x % 3]).unwrap() = (x * 4 + 1) as u8;
+        }
+
+        let pattern = Pattern::from(grid).with_rules("B2/S/C255".parse().unwrap());
+        let back = round_trip(&pattern);
+
+        assert_eq!(back.grid.as_slice(), pattern.grid.as_slice());
+        assert_eq!(back.grid.boundary, Boundary::Klein);
+    }
+
+    #[test]
+    fn rejects_malformed_files()
+    {
+        let bad = [
+            "bo$2bo$3o!",
+            "x = 3\nbo!",
+            "x = 0, y = 3\n!",
+            "x = 3, y = 3, z = 1\n!",
+            "x = 3, y = 3, rule = B3/S23:S5\n!",
+            "x = 3, y = 3, rule = Q3\n!",
+            "x = 100000, y = 100000\n!",
+            "x = 3, y = 3\n4o!",
+            "x = 3, y = 3\n3$o!",
+            "x = 3, y = 3\nbz!",
+            "x = 3, y = 3\n99999999999999999999o!",
+            "x = 3, y = 3\n9223372036854775807$9223372036854775807$o!",
+            "x = 3, y = 3\nzA!",
+        ];
+
+        for text in bad
+        {
+            assert!(Pattern::read_rle(text.as_bytes()).is_err(), "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use mint::Point2;
+
+use crate::Grid;
+
+/// Which cells touching a cell count as joined to it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Connectivity
+{
+    /// Cells sharing an edge, as in `neighbors_of_limited`
+    #[default]
+    Four,
+    /// Cells sharing an edge or a corner, as in `neighbors_of`
+    Eight,
+}
+
+impl Connectivity
+{
+    /// The points joined to `p`, which may lie outside the grid
+    pub fn neighbors_of(self, p: impl Into<Point2<isize>>) -> impl Iterator<Item = Point2<isize>>
+    {
+        let neighbors: Vec<_> = match self
+        {
+            Connectivity::Four => Grid::neighbors_of_limited(p).collect(),
+            Connectivity::Eight => Grid::neighbors_of(p).collect(),
+        };
+
+        neighbors.into_iter()
+    }
+}
+
+/// One connected region found by `label_regions`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region
+{
+    /// The value this region's cells hold in the label grid
+    pub label: u32,
+    pub size: usize,
+    /// Top-left corner of the bounding box
+    pub min: Point2<isize>,
+    /// Bottom-right corner of the bounding box, inclusive
+    pub max: Point2<isize>,
+    /// The region's first cell in row-major order
+    pub representative: Point2<isize>,
+}
+
+/// A grid of region labels, where `0` marks cells outside every region and
+/// region `n` is `regions[n - 1]`
+#[derive(Clone, Debug)]
+pub struct RegionLabels
+{
+    pub labels: Grid<u32>,
+    pub regions: Vec<Region>,
+}
+
+impl<T> Grid<T>
+{
+    /// Every cell reachable from `start` through cells `include` accepts,
+    /// including `start` itself unless it isn't accepted either.
+    /// Regions join across the grid's boundary when it wraps or mirrors.
+    pub fn flood_region_by(&self, start: impl Into<Point2<isize>>, connectivity: Connectivity, include: impl Fn(&T) -> bool) -> Vec<Point2<isize>>
+    {
+        let mut seen = Grid::new_filled(self.width, self.height, false);
+        self.flood(start.into(), connectivity, &include, &mut seen)
+    }
+
+    /// the flood behind `flood_region_by`, skipping and marking cells in `seen`
+    /// so labelling can share one across every region
+    fn flood(&self, start: Point2<isize>, connectivity: Connectivity, include: &impl Fn(&T) -> bool, seen: &mut Grid<bool>) -> Vec<Point2<isize>>
+    {
+        if !self.index(start).is_some_and(include) || *seen.index(start).unwrap()
+        {
+            return Vec::new();
+        }
+        *seen.index_mut(start).unwrap() = true;
+
+        let mut region = Vec::new();
+        let mut stack = vec![start];
+        while let Some(p) = stack.pop()
+        {
+            region.push(p);
+
+            for n in self.valid_neighbors_of(connectivity.neighbors_of(p))
+            {
+                let seen = seen.index_mut(n).unwrap();
+                if !*seen && include(self.index(n).unwrap())
+                {
+                    *seen = true;
+                    stack.push(n);
+                }
+            }
+        }
+
+        region
+    }
+
+    /// Paint-bucket fill: set every cell connected to `start` holding the same value as it to `value`.
+    /// Returns how many cells were filled.
+    pub fn flood_fill(&mut self, start: impl Into<Point2<isize>>, value: T, connectivity: Connectivity) -> usize
+    where
+        T: Clone + PartialEq
+    {
+        let start = start.into();
+        let Some(target) = self.index(start).cloned()
+        else
+        {
+            return 0;
+        };
+
+        let region = self.flood_region_by(start, connectivity, |v| *v == target);
+        for &p in &region
+        {
+            *self.index_mut(p).unwrap() = value.clone();
+        }
+
+        region.len()
+    }
+
+    /// Label every connected region of cells that `include` accepts
+    pub fn label_regions_by(&self, connectivity: Connectivity, include: impl Fn(&T) -> bool) -> RegionLabels
+    {
+        let mut labels = Grid::new(self.width, self.height).with_boundary(self.boundary);
+        let mut regions = Vec::new();
+        let mut seen = Grid::new_filled(self.width, self.height, false);
+
+        for p in self.points()
+        {
+            let cells = self.flood(p, connectivity, &include, &mut seen);
+            if cells.is_empty()
+            {
+                continue;
+            }
+
+            let label = regions.len() as u32 + 1;
+
+            let mut region = Region { label, size: cells.len(), min: p, max: p, representative: p };
+            for &c in &cells
+            {
+                *labels.index_mut(c).unwrap() = label;
+                region.min = [region.min.x.min(c.x), region.min.y.min(c.y)].into();
+                region.max = [region.max.x.max(c.x), region.max.y.max(c.y)].into();
+            }
+            regions.push(region);
+        }
+
+        RegionLabels { labels, regions }
+    }
+}
+
+impl Grid
+{
+    /// The open cells (`0`) connected to `start`
+    pub fn flood_region(&self, start: impl Into<Point2<isize>>, connectivity: Connectivity) -> Vec<Point2<isize>>
+    {
+        self.flood_region_by(start, connectivity, |&v| v == 0)
+    }
+
+    /// Label the connected regions of open cells (`0`), such as the separate caves in an automaton's output
+    pub fn label_regions(&self, connectivity: Connectivity) -> RegionLabels
+    {
+        self.label_regions_by(connectivity, |&v| v == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{tests::soup, Boundary};
+
+    /// region of every open cell, found by spreading the smallest row-major index until nothing changes
+    fn naive_labels(grid: &Grid, connectivity: Connectivity) -> Vec<Option<usize>>
+    {
+        let mut labels: Vec<Option<usize>> = grid.as_slice().iter().enumerate().map(|(i, &v)| (v == 0).then_some(i)).collect();
+        let index = |p: Point2<isize>| (p.x + p.y * grid.width) as usize;
+
+        let mut changed = true;
+        while changed
+        {
+            changed = false;
+            for p in grid.points()
+            {
+                let Some(mut label) = labels[index(p)] else { continue };
+                for n in connectivity.neighbors_of(p).filter_map(|n| grid.dim().resolve(n))
+                {
+                    if let Some(other) = labels[index(n)]
+                    {
+                        label = label.min(other);
+                    }
+                }
+
+                if Some(label) != labels[index(p)]
+                {
+                    labels[index(p)] = Some(label);
+                    changed = true;
+                }
+            }
+        }
+
+        labels
+    }
+
+    #[test]
+    fn labels_match_a_naive_search()
+    {
+        for boundary in [Boundary::Dead, Boundary::Wrap]
+        {
+            for connectivity in [Connectivity::Four, Connectivity::Eight]
+            {
+                for seed in 0..5
+                {
+                    let grid = soup(17, 17, 17, seed).with_boundary(boundary);
+                    let found = grid.label_regions(connectivity);
+                    let naive = naive_labels(&grid, connectivity);
+
+                    // labels are handed out in row-major order of each region's first cell
+                    let mut firsts: Vec<usize> = naive.iter().flatten().copied().collect();
+                    firsts.sort();
+                    firsts.dedup();
+                    assert_eq!(found.regions.len(), firsts.len());
+
+                    for (region, &first) in found.regions.iter().zip(&firsts)
+                    {
+                        let cells: Vec<Point2<isize>> = grid.points().filter(|p| naive[(p.x + p.y * 17) as usize] == Some(first)).collect();
+                        assert_eq!(region.size, cells.len());
+                        assert_eq!(region.representative, cells[0]);
+                        assert_eq!(region.min, [cells.iter().map(|c| c.x).min().unwrap(), cells.iter().map(|c| c.y).min().unwrap()].into());
+                        assert_eq!(region.max, [cells.iter().map(|c| c.x).max().unwrap(), cells.iter().map(|c| c.y).max().unwrap()].into());
+                        assert!(cells.iter().all(|&c| *found.labels.index(c).unwrap() == region.label));
+                    }
+
+                    // walls stay unlabelled
+                    for p in grid.points()
+                    {
+                        assert_eq!(*found.labels.index(p).unwrap() == 0, *grid.index(p).unwrap() != 0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wrapping_joins_regions_across_edges()
+    {
+        let grid: Grid = ".O.\nOOO\n.O.".parse().unwrap();
+        assert_eq!(grid.label_regions(Connectivity::Four).regions.len(), 4);
+        assert_eq!(grid.label_regions(Connectivity::Eight).regions.len(), 4);
+
+        let wrapped = grid.with_boundary(Boundary::Wrap);
+        let labels = wrapped.label_regions(Connectivity::Four);
+        assert_eq!(labels.regions.len(), 1);
+        assert_eq!(labels.regions[0].size, 4);
+        assert_eq!((labels.regions[0].min, labels.regions[0].max), ([0, 0].into(), [2, 2].into()));
+    }
+
+    #[test]
+    fn flood_fill_repaints_one_region()
+    {
+        let mut grid: Grid = "..O.\nOOO.\n....".parse().unwrap();
+        assert_eq!(grid.flood_fill([0, 0], 5, Connectivity::Four), 2);
+        assert_eq!(grid.as_slice(), [5, 5, 1, 0, 1, 1, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(grid.flood_fill([9, 9], 5, Connectivity::Four), 0);
+    }
+}
@@ -0,0 +1,274 @@
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Automaton, ConstRules, DynamicRules, Grid, GridDim, HenselRules, Kernel, LtlRules};
+
+/// The most cells a deserialized grid may declare, since a few bytes of runs can otherwise ask for terabytes
+pub const GRID_MAX_CELLS: isize = 1 << 26;
+
+/// Cells are stored as `(count, value)` runs, which keeps mostly empty levels small
+#[derive(Serialize)]
+#[serde(rename = "Grid")]
+struct GridRef<'a, T>
+{
+    dim: GridDim,
+    cells: Vec<(usize, &'a T)>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Grid")]
+struct GridRepr<T>
+{
+    dim: GridDim,
+    cells: Vec<(usize, T)>,
+}
+
+impl<T> Serialize for Grid<T>
+where
+    T: Serialize + PartialEq
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut cells: Vec<(usize, &T)> = Vec::new();
+        for v in &self.array
+        {
+            match cells.last_mut()
+            {
+                Some((n, last)) if *last == v => *n += 1,
+                _ => cells.push((1, v)),
+            }
+        }
+
+        GridRef { dim: self.dim, cells }.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Grid<T>
+where
+    T: Deserialize<'de> + Clone
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        let GridRepr { dim, cells } = GridRepr::deserialize(deserializer)?;
+
+        if dim.width <= 0 || dim.height <= 0
+        {
+            return Err(D::Error::custom(format!("grid is {}x{}, both sides must be positive", dim.width, dim.height)));
+        }
+
+        let expected = dim.width.checked_mul(dim.height)
+            .filter(|&area| area <= GRID_MAX_CELLS)
+            .ok_or_else(|| D::Error::custom(format!("a {}x{} grid is more than {} cells", dim.width, dim.height, GRID_MAX_CELLS)))? as usize;
+
+        // check every run fits in what's left before expanding any, so bad counts can't overflow or allocate
+        let total = cells.iter().try_fold(0usize, |total, &(n, _)|
+        {
+            total.checked_add(n).filter(|&t| t <= expected)
+        });
+        if total != Some(expected)
+        {
+            return Err(D::Error::custom(format!("runs don't hold the {} cells a {}x{} grid needs", expected, dim.width, dim.height)));
+        }
+
+        let array = cells.into_iter().flat_map(|(n, v)| std::iter::repeat_n(v, n)).collect();
+        Ok(Grid { dim, array })
+    }
+}
+
+/// Rules are written as their `B3/S23` string
+impl Serialize for DynamicRules
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DynamicRules
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Larger-than-Life rules are written as their `R5,C0,M1,S34..58,B34..45,NM` string
+impl Serialize for LtlRules
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LtlRules
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Isotropic rules are written in Hensel notation, like `B2-a/S12`
+impl Serialize for HenselRules
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HenselRules
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+impl<const B: usize, const S: usize> Serialize for ConstRules<B, S>
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    {
+        DynamicRules::from(self.clone()).serialize(serializer)
+    }
+}
+
+/// Only rules with exactly `B` birth and `S` survive counts and no decay states fit
+impl<'de, const B: usize, const S: usize> Deserialize<'de> for ConstRules<B, S>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        let rules = DynamicRules::deserialize(deserializer)?;
+        if rules.states > 2
+        {
+            return Err(D::Error::custom(format!("{} has decay states, which ConstRules can't hold", rules)));
+        }
+
+        match (rules.birth.as_slice().try_into(), rules.survive.as_slice().try_into())
+        {
+            (Ok(birth), Ok(survive)) => Ok(ConstRules::new(birth, survive)),
+            _ => Err(D::Error::custom(format!("{} doesn't have {} birth and {} survive counts", rules, B, S))),
+        }
+    }
+}
+
+/// The spare buffer isn't stored, it's rebuilt from `grid` when loading
+#[derive(Serialize)]
+#[serde(rename = "Automaton")]
+struct AutomatonRef<'a, R, G>
+{
+    grid: &'a G,
+    rules: &'a R,
+    generation: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kernel: &'a Option<Kernel<u32>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Automaton")]
+struct AutomatonRepr<R, G>
+{
+    grid: G,
+    rules: R,
+    generation: u64,
+    #[serde(default)]
+    kernel: Option<Kernel<u32>>,
+}
+
+impl<R, G> Serialize for Automaton<R, G>
+where
+    R: Serialize,
+    G: Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        AutomatonRef { grid: &self.grid, rules: &self.rules, generation: self.generation, kernel: &self.kernel }.serialize(serializer)
+    }
+}
+
+impl<'de, R, G> Deserialize<'de> for Automaton<R, G>
+where
+    R: Deserialize<'de>,
+    G: Deserialize<'de> + Clone
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        let AutomatonRepr { grid, rules, generation, kernel } = AutomatonRepr::<R, G>::deserialize(deserializer)?;
+
+        Ok(Automaton
+        {
+            other_grid: grid.clone(),
+            grid,
+            rules,
+            generation,
+            kernel,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{tests::soup, Automaton, Boundary, DynamicRules, Grid, Kernel};
+
+    #[test]
+    fn grid_round_trips()
+    {
+        let grid: Grid = "O..\n.OO\nOOO".parse().unwrap();
+        let grid = grid.with_boundary(Boundary::Wrap);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let back: Grid = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.as_slice(), grid.as_slice());
+        assert_eq!(back.dim(), grid.dim());
+    }
+
+    #[test]
+    fn rules_round_trip()
+    {
+        let rules: DynamicRules = "B2/S345/C4".parse().unwrap();
+        let json = serde_json::to_string(&rules).unwrap();
+        assert_eq!(json, "\"B2/S345/C4\"");
+        assert_eq!(serde_json::from_str::<DynamicRules>(&json).unwrap().to_string(), rules.to_string());
+    }
+
+    #[test]
+    fn grid_rejects_bad_sizes_and_runs()
+    {
+        let dim = |w: &str, h: &str| format!(r#""dim":{{"width":{},"height":{},"boundary":"Dead"}}"#, w, h);
+        let bad = [
+            format!(r#"{{{},"cells":[[4,0]]}}"#, dim("0", "4")),
+            format!(r#"{{{},"cells":[[4,0]]}}"#, dim("9223372036854775807", "4")),
+            format!(r#"{{{},"cells":[[18446744073709551615,0],[2,0]]}}"#, dim("2", "2")),
+            format!(r#"{{{},"cells":[[3,0]]}}"#, dim("2", "2")),
+            format!(r#"{{{},"cells":[[3,0],[2,1]]}}"#, dim("2", "2")),
+            format!(r#"{{{},"cells":[[1000000000000,0]]}}"#, dim("1000000", "1000000")),
+        ];
+
+        for json in bad
+        {
+            assert!(serde_json::from_str::<Grid>(&json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn automaton_round_trips()
+    {
+        let mut at = Automaton::new(soup(20, 20, 12, 0).with_boundary(Boundary::Mirror), "B2/S345/C4".parse::<DynamicRules>().unwrap());
+        at.set_kernel(Kernel::von_neumann(2));
+        at.step_n(3);
+
+        let json = serde_json::to_string(&at).unwrap();
+        let mut back: Automaton<DynamicRules> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.grid.as_slice(), at.grid.as_slice());
+        assert_eq!(back.grid.dim(), at.grid.dim());
+        assert_eq!(back.rules.to_string(), at.rules.to_string());
+        assert_eq!(back.generation(), 3);
+
+        // the kernel came along, so both carry on the same way
+        at.step_n(4);
+        back.step_n(4);
+        assert_eq!(back.grid.as_slice(), at.grid.as_slice());
+    }
+}
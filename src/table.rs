@@ -0,0 +1,169 @@
+use crate::IsRules;
+
+/// A set of neighbour counts `0..=255` packed into bits, so checking a count is a single lookup
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RuleMask([u64; 4]);
+
+impl RuleMask
+{
+    pub fn from_counts<'a>(counts: impl Iterator<Item = &'a u8>) -> Self
+    {
+        let mut mask = RuleMask::default();
+        for &n in counts
+        {
+            mask.0[n as usize / 64] |= 1 << (n % 64);
+        }
+
+        mask
+    }
+
+    pub fn contains(&self, count: u32) -> bool
+    {
+        count < 256 && self.0[count as usize / 64] >> (count % 64) & 1 == 1
+    }
+
+    /// Just the counts `0..=8` a Moore neighbourhood can reach, bit `n` set for count `n`
+    pub fn moore_bits(&self) -> u16
+    {
+        (self.0[0] & 0x1ff) as u16
+    }
+}
+
+/// Rules compiled once into birth and survive masks, for stepping loops that look them up per cell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RuleTable
+{
+    pub birth: RuleMask,
+    pub survive: RuleMask,
+    /// Above 2 for Generations rules, see `next_state`
+    pub states: u8,
+}
+
+impl RuleTable
+{
+    pub fn new(rules: &(impl IsRules + ?Sized)) -> Self
+    {
+        RuleTable
+        {
+            birth: RuleMask::from_counts(rules.get_birth()),
+            survive: RuleMask::from_counts(rules.get_surive()),
+            states: rules.states(),
+        }
+    }
+
+    /// The table for a stepper that only knows live and dead cells, like `BitGrid` and `HashLife`
+    pub(crate) fn two_state(rules: &(impl IsRules + ?Sized)) -> Self
+    {
+        let table = rules.table();
+        assert!(table.states <= 2, "Generations rules need a stepper that keeps each cell's state");
+        table
+    }
+
+    /// Whether a cell in `state` counts as a live neighbour. Decaying cells of Generations rules don't.
+    pub fn alive(&self, state: u8) -> bool
+    {
+        match self.states
+        {
+            0..=2 => state > 0,
+            _ => state == 1,
+        }
+    }
+
+    /// Whether a cell is alive next generation, given whether it is now and its live neighbour count
+    pub fn next(&self, alive: bool, count: u32) -> bool
+    {
+        match alive
+        {
+            false => self.birth.contains(count),
+            true => self.survive.contains(count),
+        }
+    }
+
+    /// The state a cell is in next generation, given its state now and its live neighbour count.
+    ///
+    /// With 2 states a surviving cell keeps whatever value it had. With more, state 1 is alive,
+    /// a live cell that doesn't survive goes to state 2 and then counts up through the decay states to 0,
+    /// and only dead cells (0) can be born.
+    pub fn next_state(&self, state: u8, count: u32) -> u8
+    {
+        advance(self.states, state, self.next(state > 0, count))
+    }
+}
+
+/// The state after `state` for a rule with `states` states, given whether the cell
+/// meets the rule's birth condition (when dead) or survive condition (when alive)
+pub(crate) fn advance(states: u8, state: u8, passes: bool) -> u8
+{
+    match state
+    {
+        // dead
+        0 => passes as u8,
+        // alive, keeping whatever value it had
+        v if states <= 2 => if passes { v } else { 0 },
+        1 => if passes { 1 } else { 2 },
+        // decaying, ignoring the neighbours
+        v => if v + 1 < states { v + 1 } else { 0 },
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{tests::soup, Automaton, BitGrid, Boundary, DynamicRules, Kernel};
+
+    #[test]
+    fn generations_cells_decay_before_dying()
+    {
+        // Star Wars
+        let table = "B2/S345/C4".parse::<DynamicRules>().unwrap().table();
+        assert_eq!(table.next_state(0, 2), 1);
+        assert_eq!(table.next_state(0, 3), 0);
+        assert_eq!(table.next_state(1, 4), 1);
+        assert_eq!(table.next_state(1, 2), 2);
+        // decaying cells ignore their neighbours
+        assert_eq!(table.next_state(2, 4), 3);
+        assert_eq!(table.next_state(3, 2), 0);
+    }
+
+    #[test]
+    fn brians_brain_steps_on_a_grid()
+    {
+        let mut at = Automaton::new_from_dims(6, 6, "B2/S/C3".parse::<DynamicRules>().unwrap());
+        *at.grid.index_mut([2, 2]).unwrap() = 1;
+        *at.grid.index_mut([3, 2]).unwrap() = 1;
+
+        at.step();
+        assert_eq!(*at.grid.index([2, 2]).unwrap(), 2);
+        assert_eq!(*at.grid.index([2, 1]).unwrap(), 1);
+        assert_eq!(*at.grid.index([3, 3]).unwrap(), 1);
+
+        at.step();
+        assert_eq!(*at.grid.index([2, 2]).unwrap(), 0);
+    }
+
+    #[test]
+    fn generations_count_the_same_through_a_moore_kernel()
+    {
+        let rules: DynamicRules = "B2/S345/C4".parse().unwrap();
+        let mut plain = Automaton::new(soup(30, 30, 20, 4).with_boundary(Boundary::Wrap), rules.clone());
+        let mut kernel = Automaton::new(plain.grid.clone(), rules);
+        kernel.set_kernel(Kernel::moore(1));
+
+        for _ in 0..10
+        {
+            plain.step();
+            kernel.step();
+            assert_eq!(kernel.grid.as_slice(), plain.grid.as_slice());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Generations rules need a stepper that keeps each cell's state")]
+    fn two_state_steppers_reject_generations_rules()
+    {
+        let grid = BitGrid::new(8, 8);
+        let mut at = Automaton::new(grid, "B2/S/C3".parse::<DynamicRules>().unwrap());
+        at.step();
+    }
+}
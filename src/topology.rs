@@ -0,0 +1,155 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// A layout of cells that can be searched and carved into mazes,
+/// independent of whether the cells are squares, hexagons or anything else.
+pub trait IsTopology
+{
+    type Point: Copy + Eq + Hash;
+
+    /// Cells that share an edge with `p` and can be stepped to from it, already inside the grid
+    fn adjacent(&self, p: Self::Point) -> impl Iterator<Item = Self::Point>;
+
+    /// A lower bound on the number of steps between `a` and `b`
+    fn distance(&self, a: Self::Point, b: Self::Point) -> isize;
+
+    /// A* search from `start` to `end`, only stepping onto cells that are `passable`.
+    ///
+    /// Returns every cell on the path except `start`.
+    fn a_star(&self, start: Self::Point, end: Self::Point, passable: impl Fn(Self::Point) -> bool) -> Option<HashSet<Self::Point>>
+    {
+        let mut prev = HashMap::from([(start, start)]);
+        let mut g_distance_from_start = HashMap::from([(start, 0)]);
+
+        // heap entries point into `points`, so the points themselves don't need to be ordered
+        let mut points = vec![start];
+        let mut frontier = BinaryHeap::from([(Reverse(self.distance(start, end)), 0, 0)]);
+
+        while let Some((_, g, i)) = frontier.pop()
+        {
+            let current = points[i];
+            if g > g_distance_from_start[&current] { continue }
+
+            if current == end
+            {
+                return Some(trace_back(start, end, &prev));
+            }
+
+            for neighbor in self.adjacent(current)
+            {
+                if !passable(neighbor) { continue }
+
+                let tentative_g_score = g + 1;
+                if tentative_g_score < *g_distance_from_start.get(&neighbor).unwrap_or(&isize::MAX)
+                {
+                    prev.insert(neighbor, current);
+                    g_distance_from_start.insert(neighbor, tentative_g_score);
+
+                    points.push(neighbor);
+                    let f = tentative_g_score + self.distance(neighbor, end);
+                    frontier.push((Reverse(f), tentative_g_score, points.len() - 1));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Grow a perfect maze out from `start` with a randomised depth-first search.
+    ///
+    /// A cell is only opened if the cell it is reached from is its sole open neighbour,
+    /// so corridors never touch and there is exactly one route between any two open cells.
+    /// Returns the open cells.
+    fn carve_maze(&self, start: Self::Point, rng: &mut impl Rng) -> HashSet<Self::Point>
+    {
+        let mut open = HashSet::from([start]);
+        let mut stack = vec![start];
+
+        while let Some(&current) = stack.last()
+        {
+            let candidates: Vec<_> =
+                self.adjacent(current)
+                .filter(|n|
+                {
+                    !open.contains(n) &&
+                    self.adjacent(*n).all(|m| m == current || !open.contains(&m))
+                })
+                .collect();
+
+            match candidates.choose(rng)
+            {
+                Some(&next) =>
+                {
+                    open.insert(next);
+                    stack.push(next);
+                },
+                None =>
+                {
+                    stack.pop();
+                },
+            }
+        }
+
+        open
+    }
+}
+
+/// start at end and trace back to start
+fn trace_back<P>(start: P, end: P, prev: &HashMap<P, P>) -> HashSet<P>
+where
+    P: Copy + Eq + Hash
+{
+    let mut pt = end;
+
+    let mut ret = HashSet::new();
+
+    while pt != start
+    {
+        ret.insert(pt);
+
+        pt = prev[&pt];
+    }
+
+    ret
+}
+
+#[cfg(test)]
+pub(crate) mod tests
+{
+    use super::*;
+
+    /// Steps from `start` to every `passable` cell it can reach through `adjacent`
+    pub(crate) fn walk<G: IsTopology>(g: &G, start: G::Point, passable: impl Fn(G::Point) -> bool) -> HashMap<G::Point, isize>
+    {
+        let mut steps = HashMap::from([(start, 0)]);
+        let mut queue = std::collections::VecDeque::from([start]);
+        while let Some(p) = queue.pop_front()
+        {
+            for n in g.adjacent(p).filter(|&n| passable(n))
+            {
+                if !steps.contains_key(&n)
+                {
+                    steps.insert(n, steps[&p] + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        steps
+    }
+
+    /// Checks the `open` cells are joined into a tree: every one reachable, and one fewer passage than cells
+    pub(crate) fn assert_perfect_maze<G: IsTopology>(g: &G, open: &HashSet<G::Point>)
+    {
+        let start = *open.iter().next().unwrap();
+        assert_eq!(walk(g, start, |p| open.contains(&p)).len(), open.len());
+
+        // each passage is seen once from either end
+        let passages = open.iter().map(|&p| g.adjacent(p).filter(|n| open.contains(n)).count()).sum::<usize>();
+        assert_eq!(passages, 2 * (open.len() - 1));
+    }
+}
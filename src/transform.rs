@@ -0,0 +1,235 @@
+use crate::{Grid, GridView, GridViewMut};
+
+impl<T> GridView<'_, T>
+where
+    T: Clone
+{
+    /// Rotate a quarter turn clockwise
+    pub fn rotate90(&self) -> Grid<T>
+    {
+        let h = self.height;
+        self.remap(self.height, self.width, |x, y| (y, h - 1 - x))
+    }
+
+    pub fn rotate180(&self) -> Grid<T>
+    {
+        let (w, h) = (self.width, self.height);
+        self.remap(w, h, |x, y| (w - 1 - x, h - 1 - y))
+    }
+
+    /// Rotate a quarter turn anticlockwise
+    pub fn rotate270(&self) -> Grid<T>
+    {
+        let w = self.width;
+        self.remap(self.height, self.width, |x, y| (w - 1 - y, x))
+    }
+
+    /// Mirror left to right
+    pub fn flip_horizontal(&self) -> Grid<T>
+    {
+        let w = self.width;
+        self.remap(self.width, self.height, |x, y| (w - 1 - x, y))
+    }
+
+    /// Mirror top to bottom
+    pub fn flip_vertical(&self) -> Grid<T>
+    {
+        let h = self.height;
+        self.remap(self.width, self.height, |x, y| (x, h - 1 - y))
+    }
+
+    /// Mirror across the top-left to bottom-right diagonal
+    pub fn transpose(&self) -> Grid<T>
+    {
+        self.remap(self.height, self.width, |x, y| (y, x))
+    }
+
+    /// Mirror across the top-right to bottom-left diagonal
+    pub fn anti_transpose(&self) -> Grid<T>
+    {
+        let (w, h) = (self.width, self.height);
+        self.remap(h, w, |x, y| (w - 1 - y, h - 1 - x))
+    }
+
+    /// All 8 rotations and reflections of the square's symmetry group, starting with an unchanged copy
+    pub fn dihedral_images(&self) -> [Grid<T>; 8]
+    {
+        [
+            self.to_grid(),
+            self.rotate90(),
+            self.rotate180(),
+            self.rotate270(),
+            self.flip_horizontal(),
+            self.flip_vertical(),
+            self.transpose(),
+            self.anti_transpose(),
+        ]
+    }
+
+    /// Whether `other` is some rotation or reflection of this view
+    pub fn eq_up_to_symmetry<'b>(&self, other: impl Into<GridView<'b, T>>) -> bool
+    where
+        T: PartialEq + 'b
+    {
+        let other = other.into().to_grid();
+
+        self.dihedral_images()
+        .iter()
+        .any(|image| image.width == other.width && image.height == other.height && image.array == other.array)
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: Clone
+{
+    pub fn rotate90(&self) -> Grid<T>
+    {
+        self.as_view().rotate90()
+    }
+
+    pub fn rotate180(&self) -> Grid<T>
+    {
+        self.as_view().rotate180()
+    }
+
+    pub fn rotate270(&self) -> Grid<T>
+    {
+        self.as_view().rotate270()
+    }
+
+    pub fn flip_horizontal(&self) -> Grid<T>
+    {
+        self.as_view().flip_horizontal()
+    }
+
+    pub fn flip_vertical(&self) -> Grid<T>
+    {
+        self.as_view().flip_vertical()
+    }
+
+    pub fn transpose(&self) -> Grid<T>
+    {
+        self.as_view().transpose()
+    }
+
+    pub fn anti_transpose(&self) -> Grid<T>
+    {
+        self.as_view().anti_transpose()
+    }
+
+    pub fn dihedral_images(&self) -> [Grid<T>; 8]
+    {
+        self.as_view().dihedral_images()
+    }
+
+    pub fn eq_up_to_symmetry<'b>(&self, other: impl Into<GridView<'b, T>>) -> bool
+    where
+        T: PartialEq + 'b
+    {
+        self.as_view().eq_up_to_symmetry(other)
+    }
+}
+
+impl<T> GridViewMut<'_, T>
+where
+    T: Clone
+{
+    pub fn rotate90(&self) -> Grid<T>
+    {
+        self.as_view().rotate90()
+    }
+
+    pub fn rotate180(&self) -> Grid<T>
+    {
+        self.as_view().rotate180()
+    }
+
+    pub fn rotate270(&self) -> Grid<T>
+    {
+        self.as_view().rotate270()
+    }
+
+    pub fn flip_horizontal(&self) -> Grid<T>
+    {
+        self.as_view().flip_horizontal()
+    }
+
+    pub fn flip_vertical(&self) -> Grid<T>
+    {
+        self.as_view().flip_vertical()
+    }
+
+    pub fn transpose(&self) -> Grid<T>
+    {
+        self.as_view().transpose()
+    }
+
+    pub fn anti_transpose(&self) -> Grid<T>
+    {
+        self.as_view().anti_transpose()
+    }
+
+    pub fn dihedral_images(&self) -> [Grid<T>; 8]
+    {
+        self.as_view().dihedral_images()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::tests::soup;
+    use crate::Grid;
+
+    fn same(a: &Grid, b: &Grid) -> bool
+    {
+        (a.width, a.height) == (b.width, b.height) && a.as_slice() == b.as_slice()
+    }
+
+    #[test]
+    fn rotations_and_flips_compose()
+    {
+        let g = soup(7, 4, 4, 12).crop([0, 0], 5, 4).unwrap();
+
+        assert!(same(&g.rotate90().rotate90().rotate90().rotate90(), &g));
+        assert!(same(&g.flip_horizontal().flip_horizontal(), &g));
+        assert!(same(&g.flip_vertical().flip_vertical(), &g));
+        assert!(same(&g.rotate90().rotate90(), &g.rotate180()));
+        assert!(same(&g.rotate90().rotate180(), &g.rotate270()));
+        assert!(same(&g.transpose(), &g.rotate90().flip_horizontal()));
+        assert!(same(&g.anti_transpose(), &g.rotate90().flip_vertical()));
+        assert!(same(&g.transpose().transpose(), &g));
+    }
+
+    #[test]
+    fn rotate90_turns_clockwise()
+    {
+        let g: Grid = "OO.\n...".parse().unwrap();
+        assert!(same(&g.rotate90(), &".O\n.O\n..".parse().unwrap()));
+        assert!(same(&g.rotate270(), &"..\nO.\nO.".parse().unwrap()));
+    }
+
+    #[test]
+    fn dihedral_images_are_distinct_for_a_lopsided_grid()
+    {
+        let g: Grid = "OO.\nO..\nO..".parse().unwrap();
+        let images = g.dihedral_images();
+        for (i, a) in images.iter().enumerate()
+        {
+            assert!(g.eq_up_to_symmetry(a));
+            assert!(images[i + 1..].iter().all(|b| !same(a, b)), "image {} repeats", i);
+        }
+
+        assert!(!g.eq_up_to_symmetry(&"OOO\n...\n...".parse::<Grid>().unwrap()));
+    }
+
+    #[test]
+    fn views_transform_only_their_window()
+    {
+        let g: Grid = "....\n.OO.\n.O..".parse().unwrap();
+        let v = g.view([1, 1], 2, 2).unwrap();
+        assert!(same(&v.rotate90(), &"OO\n.O".parse().unwrap()));
+        assert!(same(&v.flip_vertical(), &"O.\nOO".parse().unwrap()));
+    }
+}
@@ -0,0 +1,365 @@
+use mint::Point2;
+use rand::Rng;
+
+use crate::{Automaton, Boundary, Grid, IsRules, IsTopology};
+
+/// Which cells of a [`TriGrid`] count as neighbours when stepping an automaton
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TriNeighborhood
+{
+    /// The 3 cells sharing an edge
+    #[default]
+    Edges,
+    /// The 12 cells sharing an edge or a corner
+    Vertices,
+}
+
+/// Triangular cells kept in a `Grid`, alternating between pointing up and down along each row.
+///
+/// The cell at `(x, y)` points up when `x + y` is even. Paths and mazes always move
+/// through edges, while `neighborhood` picks what the automaton counts.
+/// Wrapping and Klein boundaries only line up when the width and height are even.
+#[derive(Clone, Debug)]
+pub struct TriGrid<T = u8>
+{
+    pub grid: Grid<T>,
+    pub neighborhood: TriNeighborhood,
+}
+
+impl<T> std::ops::Deref for TriGrid<T>
+{
+    type Target = Grid<T>;
+
+    fn deref(&self) -> &Self::Target
+    {
+        &self.grid
+    }
+}
+
+impl<T> std::ops::DerefMut for TriGrid<T>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target
+    {
+        &mut self.grid
+    }
+}
+
+impl<T> TriGrid<T>
+{
+    pub fn new(width: isize, height: isize) -> Self
+    where
+        T: Default + Clone
+    {
+        TriGrid::from_grid(Grid::new(width, height))
+    }
+
+    pub fn from_grid(grid: Grid<T>) -> Self
+    {
+        TriGrid { grid, neighborhood: TriNeighborhood::default() }
+    }
+
+    pub fn with_neighborhood(mut self, neighborhood: TriNeighborhood) -> Self
+    {
+        self.neighborhood = neighborhood;
+        self
+    }
+}
+
+impl<T> TriGrid<T>
+{
+    /// The cell `p` refers to under the boundary, like `GridDim::resolve`.
+    ///
+    /// A Klein bottle mirrors each row about the centre of its first cell rather than between two cells,
+    /// since that keeps triangles pointing the same way, so a base on the twisted edge still meets a base.
+    pub fn resolve(&self, p: impl Into<Point2<isize>>) -> Option<Point2<isize>>
+    {
+        let p = p.into();
+        match self.boundary
+        {
+            Boundary::Klein if p.y.div_euclid(self.height) % 2 != 0 => self.dim.resolve([p.x - 1, p.y]),
+            _ => self.dim.resolve(p),
+        }
+    }
+}
+
+impl TriGrid
+{
+    pub fn is_up(p: impl Into<Point2<isize>>) -> bool
+    {
+        let p = p.into();
+        (p.x + p.y).rem_euclid(2) == 0
+    }
+
+    /// The cells around `p` in the given neighborhood, which may lie outside the grid
+    pub fn neighbors_of(p: impl Into<Point2<isize>>, neighborhood: TriNeighborhood) -> impl Iterator<Item = Point2<isize>>
+    {
+        let p = p.into();
+        let x = p.x;
+        let y = p.y;
+
+        // up triangles share their base with the row below, down triangles with the row above
+        let (base, apex) = if TriGrid::is_up(p) { (y + 1, y - 1) } else { (y - 1, y + 1) };
+
+        let points: Vec<Point2<isize>> = match neighborhood
+        {
+            TriNeighborhood::Edges => vec![[x-1, y].into(), [x+1, y].into(), [x, base].into()],
+            TriNeighborhood::Vertices =>
+            {
+                (x-1..=x+1).map(|x| [x, apex].into())
+                .chain([x-2, x-1, x+1, x+2].map(|x| [x, y].into()))
+                .chain((x-2..=x+2).map(|x| [x, base].into()))
+                .collect()
+            },
+        };
+
+        points.into_iter()
+    }
+
+    /// Count the live neighbours of `p`, with cells past the edges decided by the boundary
+    pub fn sum_neighbors(&self, p: impl Into<Point2<isize>>) -> u8
+    {
+        self.sum_neighbors_by(p, |v| v > 0)
+    }
+
+    /// Count the neighbours of `p` whose value is `alive`, with cells past the edges decided by the boundary
+    pub fn sum_neighbors_by(&self, p: impl Into<Point2<isize>>, alive: impl Fn(u8) -> bool) -> u8
+    {
+        TriGrid::neighbors_of(p, self.neighborhood)
+        .fold(0, |acc, p|
+        {
+            acc + match self.resolve(p)
+            {
+                Some(p) => alive(*self.index(p).unwrap()) as u8,
+                None => (self.boundary == Boundary::Alive) as u8,
+            }
+        })
+    }
+
+    pub fn find_path_with_a_star(&self, start: Point2<isize>, end: Point2<isize>) -> Option<std::collections::HashSet<Point2<isize>>>
+    {
+        self.a_star(start, end, |p| *self.index(p).unwrap() == 0)
+    }
+
+    /// A perfect triangle maze of open (0) corridors carved out of solid wall (1)
+    pub fn generate_maze(width: isize, height: isize, rng: &mut impl Rng) -> Self
+    {
+        let mut tri = TriGrid::from_grid(Grid::new_filled(width, height, 1));
+        for p in tri.carve_maze([0, 0].into(), rng)
+        {
+            *tri.index_mut(p).unwrap() = 0;
+        }
+
+        tri
+    }
+}
+
+impl<T> IsTopology for TriGrid<T>
+{
+    type Point = Point2<isize>;
+
+    fn adjacent(&self, p: Self::Point) -> impl Iterator<Item = Self::Point>
+    {
+        TriGrid::neighbors_of(p, TriNeighborhood::Edges).filter_map(|n| self.resolve(n))
+    }
+
+    /// every step through an edge moves one row or one column, so city block distance never overestimates
+    fn distance(&self, a: Self::Point, b: Self::Point) -> isize
+    {
+        match self.boundary
+        {
+            // the copies of `b` past the twisted edge sit at `-b.x`, see `resolve`
+            Boundary::Klein =>
+            {
+                let (w, h) = (self.width, self.height);
+                let wrapped = |d: isize| isize::min(d.rem_euclid(w), w - d.rem_euclid(w));
+                let dy = (a.y - b.y).abs();
+                isize::min(wrapped(a.x - b.x) + dy, wrapped(a.x + b.x) + h - dy)
+            },
+            _ => self.cityblock_through_boundary(a, b),
+        }
+    }
+}
+
+impl<R> Automaton<R, TriGrid>
+where
+    R: IsRules
+{
+    pub fn set_boundary(&mut self, boundary: Boundary)
+    {
+        self.grid.dim.boundary = boundary;
+        self.other_grid.dim.boundary = boundary;
+    }
+
+    /// Step every cell by its 3 edge or 12 vertex neighbours, depending on the grid's neighborhood.
+    /// Survivors keep their value, and Generations rules decay cells the same as on a `Grid`.
+    pub fn step(&mut self)
+    {
+        let table = self.rules.table();
+        // `grid` is public, so it may have been swapped for one of another size since the last step
+        if self.other_grid.array.len() != self.grid.array.len()
+        {
+            self.other_grid = self.grid.clone();
+        }
+        self.other_grid.dim = self.grid.dim;
+        self.other_grid.neighborhood = self.grid.neighborhood;
+
+        for p in self.grid.points()
+        {
+            let num_alive_neighbors = self.grid.sum_neighbors_by(p, |v| table.alive(v));
+            *self.other_grid.index_mut(p).unwrap() = table.next_state(*self.grid.index(p).unwrap(), num_alive_neighbors as u32);
+        }
+
+        std::mem::swap(&mut self.grid, &mut self.other_grid);
+        self.generation += 1;
+    }
+}
+
+impl_stepper!(<R: IsRules> Automaton<R, TriGrid>);
+
+#[cfg(test)]
+mod tests
+{
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::topology::tests::{assert_perfect_maze, walk};
+    use crate::DynamicRules;
+
+    const BOUNDARIES: [Boundary; 5] = [Boundary::Dead, Boundary::Alive, Boundary::Wrap, Boundary::Mirror, Boundary::Klein];
+
+    /// whether a dead cell with all 12 vertex neighbours alive is born under `rule`
+    fn born_in_full_neighborhood(rule: &str) -> bool
+    {
+        let mut grid = TriGrid::from_grid(Grid::new_filled(7, 5, 1u8).with_boundary(Boundary::Alive))
+            .with_neighborhood(TriNeighborhood::Vertices);
+        *grid.index_mut([3, 2]).unwrap() = 0;
+        assert_eq!(grid.sum_neighbors([3, 2]), 12);
+
+        let mut at = Automaton::new(grid, rule.parse::<DynamicRules>().unwrap());
+        at.step();
+        *at.grid.index([3, 2]).unwrap() == 1
+    }
+
+    #[test]
+    fn vertex_rules_reach_12_from_a_rule_string()
+    {
+        assert!(born_in_full_neighborhood("B12,/S"));
+        assert!(born_in_full_neighborhood("B2,12/S"));
+        assert!(!born_in_full_neighborhood("B11,/S"));
+        assert!(!born_in_full_neighborhood("B12/S"));
+    }
+
+    #[test]
+    fn edge_neighbours_share_a_side()
+    {
+        // (2, 2) points up, so its base is on the row below; (3, 2) points down
+        let up: Vec<Point2<isize>> = TriGrid::neighbors_of([2, 2], TriNeighborhood::Edges).collect();
+        assert_eq!(up, [[1, 2].into(), [3, 2].into(), [2, 3].into()]);
+
+        let down: Vec<Point2<isize>> = TriGrid::neighbors_of([3, 2], TriNeighborhood::Edges).collect();
+        assert_eq!(down, [[2, 2].into(), [4, 2].into(), [3, 1].into()]);
+    }
+
+    #[test]
+    fn neighbours_are_mutual_under_every_boundary()
+    {
+        for boundary in BOUNDARIES
+        {
+            for neighborhood in [TriNeighborhood::Edges, TriNeighborhood::Vertices]
+            {
+                let tri = TriGrid::<u8>::from_grid(Grid::new(6, 4).with_boundary(boundary)).with_neighborhood(neighborhood);
+                let around = |p: Point2<isize>| -> Vec<Point2<isize>>
+                {
+                    TriGrid::neighbors_of(p, neighborhood).filter_map(|n| tri.resolve(n)).collect()
+                };
+
+                for a in tri.points()
+                {
+                    for b in around(a)
+                    {
+                        assert!(around(b).contains(&a), "{:?} {:?}: {:?} to {:?} is one way", boundary, neighborhood, a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distance_never_overestimates()
+    {
+        for boundary in BOUNDARIES
+        {
+            let tri = TriGrid::<u8>::from_grid(Grid::new(8, 6).with_boundary(boundary));
+            for a in tri.points()
+            {
+                for (b, steps) in walk(&tri, a, |_| true)
+                {
+                    assert!(tri.distance(a, b) <= steps, "{:?} from {:?} to {:?}", boundary, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_star_finds_shortest_paths_around_walls()
+    {
+        // a wall down the middle with a gap at the bottom
+        let mut tri = TriGrid::new(10, 6);
+        for y in 0..5
+        {
+            *tri.index_mut([5, y]).unwrap() = 1;
+        }
+
+        let (start, end) = (Point2 { x: 0, y: 0 }, Point2 { x: 9, y: 0 });
+        let steps = walk(&tri, start, |p| *tri.index(p).unwrap() == 0)[&end];
+        let path = tri.find_path_with_a_star(start, end).unwrap();
+
+        assert_eq!(path.len() as isize, steps);
+        assert!(path.contains(&Point2 { x: 5, y: 5 }));
+        assert!(path.iter().all(|&p| *tri.index(p).unwrap() == 0));
+
+        *tri.index_mut([5, 5]).unwrap() = 1;
+        assert_eq!(tri.find_path_with_a_star(start, end), None);
+    }
+
+    #[test]
+    fn mazes_are_spanning_trees_of_open_cells()
+    {
+        for seed in 0..4
+        {
+            let maze = TriGrid::generate_maze(12, 8, &mut rand::rngs::StdRng::seed_from_u64(seed));
+            let open = maze.points().filter(|&p| *maze.index(p).unwrap() == 0).collect();
+            assert_perfect_maze(&maze, &open);
+        }
+    }
+
+    #[test]
+    fn survivors_keep_their_values_and_generations_decay()
+    {
+        // an up triangle and the three cells sharing its sides
+        let mut tri = TriGrid::new(8, 6);
+        for (p, v) in [([2, 2], 5), ([1, 2], 7), ([3, 2], 9), ([2, 3], 11)]
+        {
+            *tri.index_mut(p).unwrap() = v;
+        }
+
+        let mut at = Automaton::new(tri.clone(), "B/S13".parse::<DynamicRules>().unwrap());
+        at.step_n(2);
+        assert_eq!(at.grid.as_slice(), tri.as_slice());
+
+        let mut at = Automaton::new(TriGrid::from_grid(tri.map(|&v| (v > 0) as u8)), "B/S3/C3".parse::<DynamicRules>().unwrap());
+        at.step();
+        // the middle sees all three, the outer three see only the middle
+        assert_eq!(*at.grid.index([2, 2]).unwrap(), 1);
+        assert_eq!(*at.grid.index([1, 2]).unwrap(), 2);
+        at.step();
+        // decaying cells aren't counted, so the middle dies too
+        assert_eq!(*at.grid.index([1, 2]).unwrap(), 0);
+        assert_eq!(*at.grid.index([2, 2]).unwrap(), 2);
+
+        at.grid = TriGrid::new(4, 10);
+        at.step();
+        assert_eq!((at.grid.width, at.grid.height), (4, 10));
+    }
+}
+
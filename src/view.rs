@@ -0,0 +1,511 @@
+use mint::Point2;
+
+use crate::{Boundary, Grid, GridDim};
+
+/// How a source cell is combined with the destination cell it lands on in `blit`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode
+{
+    /// Overwrite the destination
+    #[default]
+    Replace,
+    /// Overwrite the destination, except where the source is zero
+    Overlay,
+    Or,
+    And,
+    Xor,
+    Max,
+    Min,
+}
+
+impl BlendMode
+{
+    pub fn apply<T>(self, dst: T, src: T) -> T
+    where
+        T: Copy + Default + PartialEq + Ord +
+            std::ops::BitOr<Output = T> + std::ops::BitAnd<Output = T> + std::ops::BitXor<Output = T>
+    {
+        match self
+        {
+            BlendMode::Replace => src,
+            BlendMode::Overlay => if src == T::default() { dst } else { src },
+            BlendMode::Or => dst | src,
+            BlendMode::And => dst & src,
+            BlendMode::Xor => dst ^ src,
+            BlendMode::Max => dst.max(src),
+            BlendMode::Min => dst.min(src),
+        }
+    }
+}
+
+/// Which part of a grid stays put when it is resized
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Anchor
+{
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor
+{
+    /// Where the top-left of an `inner` sized box goes inside an `outer` one
+    pub fn offset(self, outer: (isize, isize), inner: (isize, isize)) -> Point2<isize>
+    {
+        let (fx, fy) = match self
+        {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        };
+
+        [(outer.0 - inner.0) * fx / 2, (outer.1 - inner.1) * fy / 2].into()
+    }
+}
+
+/// dimensions of a `width * height` window at `at`, if it fits inside `dim`
+fn window(dim: &GridDim, at: Point2<isize>, width: isize, height: isize) -> Option<GridDim>
+{
+    let fits =
+        width > 0 && height > 0 &&
+        dim.contains(at) &&
+        dim.contains([at.x + width - 1, at.y + height - 1]);
+
+    fits.then_some(GridDim { width, height, boundary: Boundary::Dead })
+}
+
+/// A borrowed rectangle of a `Grid`, addressed from its own top-left corner
+#[derive(Debug)]
+pub struct GridView<'a, T = u8>
+{
+    grid: &'a Grid<T>,
+    origin: Point2<isize>,
+    dim: GridDim,
+}
+
+impl<T> Clone for GridView<'_, T>
+{
+    fn clone(&self) -> Self
+    {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+impl<T> std::ops::Deref for GridView<'_, T>
+{
+    type Target = GridDim;
+
+    fn deref(&self) -> &Self::Target
+    {
+        &self.dim
+    }
+}
+
+impl<'a, T> From<&'a Grid<T>> for GridView<'a, T>
+{
+    fn from(grid: &'a Grid<T>) -> Self
+    {
+        grid.as_view()
+    }
+}
+
+impl<'a, T> GridView<'a, T>
+{
+    /// Where this view's top-left cell sits in the underlying grid
+    pub fn origin(&self) -> Point2<isize>
+    {
+        self.origin
+    }
+
+    /// The window's size, which is fixed when the view is made
+    pub fn dim(&self) -> GridDim
+    {
+        self.dim
+    }
+
+    pub fn index(&self, p: impl Into<Point2<isize>>) -> Option<&'a T>
+    {
+        let p = p.into();
+        if !self.dim.contains(p) { return None }
+
+        self.grid.index([self.origin.x + p.x, self.origin.y + p.y])
+    }
+
+    /// A smaller window inside this one
+    pub fn view(&self, at: impl Into<Point2<isize>>, width: isize, height: isize) -> Option<GridView<'a, T>>
+    {
+        let at = at.into();
+        let dim = window(&self.dim, at, width, height)?;
+
+        Some(GridView { grid: self.grid, origin: [self.origin.x + at.x, self.origin.y + at.y].into(), dim })
+    }
+
+    /// Copy the window out into its own grid, keeping the source grid's boundary
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone
+    {
+        self.remap(self.width, self.height, |x, y| (x, y))
+    }
+
+    /// A new `width * height` grid where cell `(x, y)` is copied from `source(x, y)` in this view,
+    /// keeping the source grid's boundary
+    pub(crate) fn remap(&self, width: isize, height: isize, source: impl Fn(isize, isize) -> (isize, isize)) -> Grid<T>
+    where
+        T: Clone
+    {
+        let array =
+            (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)|
+            {
+                let (sx, sy) = source(x, y);
+                self.index([sx, sy]).unwrap().clone()
+            })
+            .collect();
+
+        Grid::from_vec(width, height, array).with_boundary(self.grid.boundary)
+    }
+}
+
+/// A mutably borrowed rectangle of a `Grid`, addressed from its own top-left corner
+#[derive(Debug)]
+pub struct GridViewMut<'a, T = u8>
+{
+    grid: &'a mut Grid<T>,
+    origin: Point2<isize>,
+    dim: GridDim,
+}
+
+impl<T> std::ops::Deref for GridViewMut<'_, T>
+{
+    type Target = GridDim;
+
+    fn deref(&self) -> &Self::Target
+    {
+        &self.dim
+    }
+}
+
+impl<T> GridViewMut<'_, T>
+{
+    pub fn origin(&self) -> Point2<isize>
+    {
+        self.origin
+    }
+
+    pub fn dim(&self) -> GridDim
+    {
+        self.dim
+    }
+
+    pub fn as_view(&self) -> GridView<'_, T>
+    {
+        GridView { grid: self.grid, origin: self.origin, dim: self.dim }
+    }
+
+    pub fn index(&self, p: impl Into<Point2<isize>>) -> Option<&T>
+    {
+        let p = p.into();
+        if !self.dim.contains(p) { return None }
+
+        self.grid.index([self.origin.x + p.x, self.origin.y + p.y])
+    }
+
+    pub fn index_mut(&mut self, p: impl Into<Point2<isize>>) -> Option<&mut T>
+    {
+        let p = p.into();
+        if !self.dim.contains(p) { return None }
+
+        self.grid.index_mut([self.origin.x + p.x, self.origin.y + p.y])
+    }
+
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone
+    {
+        for y in 0..self.height
+        {
+            for x in 0..self.width
+            {
+                *self.index_mut([x, y]).unwrap() = value.clone();
+            }
+        }
+    }
+
+    /// Combine every cell of `src` with the cell it lands on when its top-left is placed at `at`.
+    /// Cells that land outside this window are skipped.
+    pub fn blit_with<'b>(&mut self, src: impl Into<GridView<'b, T>>, at: impl Into<Point2<isize>>, mut f: impl FnMut(&mut T, &T))
+    where
+        T: 'b
+    {
+        let src = src.into();
+        let at = at.into();
+
+        for y in 0..src.height
+        {
+            for x in 0..src.width
+            {
+                if let Some(dst) = self.index_mut([at.x + x, at.y + y])
+                {
+                    f(dst, src.index([x, y]).unwrap());
+                }
+            }
+        }
+    }
+
+    pub fn blit<'b>(&mut self, src: impl Into<GridView<'b, T>>, at: impl Into<Point2<isize>>, blend_mode: BlendMode)
+    where
+        T: 'b + Copy + Default + PartialEq + Ord +
+            std::ops::BitOr<Output = T> + std::ops::BitAnd<Output = T> + std::ops::BitXor<Output = T>
+    {
+        self.blit_with(src, at, |dst, &src| *dst = blend_mode.apply(*dst, src));
+    }
+}
+
+impl<T> Grid<T>
+{
+    pub fn as_view(&self) -> GridView<'_, T>
+    {
+        GridView { grid: self, origin: [0, 0].into(), dim: GridDim { boundary: Boundary::Dead, ..self.dim } }
+    }
+
+    pub fn as_view_mut(&mut self) -> GridViewMut<'_, T>
+    {
+        let dim = GridDim { boundary: Boundary::Dead, ..self.dim };
+        GridViewMut { grid: self, origin: [0, 0].into(), dim }
+    }
+
+    /// Borrow the `width * height` window with its top-left at `at`, or `None` if it doesn't fit
+    pub fn view(&self, at: impl Into<Point2<isize>>, width: isize, height: isize) -> Option<GridView<'_, T>>
+    {
+        let at = at.into();
+        let dim = window(&self.dim, at, width, height)?;
+
+        Some(GridView { grid: self, origin: at, dim })
+    }
+
+    pub fn view_mut(&mut self, at: impl Into<Point2<isize>>, width: isize, height: isize) -> Option<GridViewMut<'_, T>>
+    {
+        let at = at.into();
+        let dim = window(&self.dim, at, width, height)?;
+
+        Some(GridViewMut { grid: self, origin: at, dim })
+    }
+
+    /// Copy out the `width * height` window with its top-left at `at`, or `None` if it doesn't fit
+    pub fn crop(&self, at: impl Into<Point2<isize>>, width: isize, height: isize) -> Option<Grid<T>>
+    where
+        T: Clone
+    {
+        self.view(at, width, height).map(|v| v.to_grid())
+    }
+
+    /// Paste `src` with its top-left at `at`, clipping whatever falls outside this grid
+    pub fn blit<'b>(&mut self, src: impl Into<GridView<'b, T>>, at: impl Into<Point2<isize>>, blend_mode: BlendMode)
+    where
+        T: 'b + Copy + Default + PartialEq + Ord +
+            std::ops::BitOr<Output = T> + std::ops::BitAnd<Output = T> + std::ops::BitXor<Output = T>
+    {
+        self.as_view_mut().blit(src, at, blend_mode);
+    }
+
+    pub fn blit_with<'b>(&mut self, src: impl Into<GridView<'b, T>>, at: impl Into<Point2<isize>>, f: impl FnMut(&mut T, &T))
+    where
+        T: 'b
+    {
+        self.as_view_mut().blit_with(src, at, f);
+    }
+
+    /// Change the size to `width * height`, keeping the contents pinned to `anchor`.
+    /// New cells are set to `value` and cells that no longer fit are dropped.
+    pub fn resize(&self, width: isize, height: isize, anchor: Anchor, value: T) -> Grid<T>
+    where
+        T: Clone
+    {
+        let mut resized = Grid::new_filled(width, height, value).with_boundary(self.boundary);
+        let at = anchor.offset((width, height), (self.width, self.height));
+
+        resized.blit_with(self, at, |dst, src| *dst = src.clone());
+        resized
+    }
+
+    /// Surround the grid with a `border` cells wide frame of `value`
+    pub fn pad(&self, border: isize, value: T) -> Grid<T>
+    where
+        T: Clone
+    {
+        assert!(border >= 0);
+        self.resize(self.width + 2 * border, self.height + 2 * border, Anchor::Center, value)
+    }
+
+    /// Repeat the grid `across` times horizontally and `down` times vertically
+    pub fn tile(&self, across: isize, down: isize) -> Grid<T>
+    where
+        T: Clone
+    {
+        assert!(across > 0);
+        assert!(down > 0);
+
+        let array =
+            (0..self.height * down)
+            .flat_map(|y| (0..self.width * across).map(move |x| (x, y)))
+            .map(|(x, y)| self.index([x % self.width, y % self.height]).unwrap().clone())
+            .collect();
+
+        Grid::from_vec(self.width * across, self.height * down, array).with_boundary(self.boundary)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn grid(s: &str) -> Grid
+    {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn views_only_reach_their_window()
+    {
+        let g = grid("O...\n.O..\n..O.\n...O");
+        let v = g.view([1, 1], 2, 2).unwrap();
+        assert_eq!((v.origin(), v.dim().width, v.dim().height), ([1, 1].into(), 2, 2));
+        assert_eq!(v.index([0, 0]), Some(&1));
+        assert_eq!(v.index([1, 0]), Some(&0));
+        assert_eq!(v.index([2, 0]), None);
+        assert_eq!(v.index([-1, 0]), None);
+
+        // a view inside a view is placed from the outer view's corner
+        let inner = v.view([1, 1], 1, 1).unwrap();
+        assert_eq!(inner.origin(), [2, 2].into());
+        assert_eq!(inner.index([0, 0]), Some(&1));
+        assert!(v.view([1, 1], 2, 1).is_none());
+
+        assert!(g.view([3, 3], 2, 1).is_none());
+        assert!(g.view([0, 0], 0, 1).is_none());
+        assert!(g.view([-1, 0], 1, 1).is_none());
+        assert_eq!(g.crop([2, 0], 2, 3).unwrap().as_slice(), grid("..\n..\nO.").as_slice());
+    }
+
+    #[test]
+    fn mutable_views_only_change_their_window()
+    {
+        let mut g = Grid::new(4, 3);
+        let mut v = g.view_mut([1, 1], 2, 2).unwrap();
+        v.fill(7);
+        assert!(v.index_mut([2, 0]).is_none());
+        *v.index_mut([1, 1]).unwrap() = 3;
+
+        assert_eq!(g.as_slice(), [0, 0, 0, 0, 0, 7, 7, 0, 0, 7, 3, 0]);
+        assert!(g.view_mut([3, 0], 2, 1).is_none());
+    }
+
+    #[test]
+    fn blend_modes_combine_cells()
+    {
+        let src = Grid::from_vec(4, 1, vec![0u8, 0b01, 0b10, 0b11]);
+        let expected = [
+            (BlendMode::Replace, [0, 0b01, 0b10, 0b11]),
+            (BlendMode::Overlay, [0b10, 0b01, 0b10, 0b11]),
+            (BlendMode::Or,      [0b10, 0b11, 0b10, 0b11]),
+            (BlendMode::And,     [0, 0, 0b10, 0b10]),
+            (BlendMode::Xor,     [0b10, 0b11, 0, 0b01]),
+            (BlendMode::Max,     [0b10, 0b10, 0b10, 0b11]),
+            (BlendMode::Min,     [0, 0b01, 0b10, 0b10]),
+        ];
+
+        for (mode, cells) in expected
+        {
+            let mut dst = Grid::new_filled(4, 1, 0b10u8);
+            dst.blit(&src, [0, 0], mode);
+            assert_eq!(dst.as_slice(), cells, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn blits_clip_at_every_edge()
+    {
+        let src = grid("OO\nOO");
+        for (at, cells) in [
+            ([-1, -1], "O..\n...\n..."),
+            ([2, 2], "...\n...\n..O"),
+            ([2, -1], "..O\n...\n..."),
+            ([-1, 2], "...\n...\nO.."),
+            ([3, 0], "...\n...\n..."),
+            ([-2, 0], "...\n...\n..."),
+            ([isize::MIN / 2, isize::MAX / 2], "...\n...\n..."),
+        ]
+        {
+            let mut dst = Grid::new(3, 3);
+            dst.blit(&src, at, BlendMode::Replace);
+            assert_eq!(dst.as_slice(), grid(cells).as_slice(), "at {:?}", at);
+        }
+
+        // a mutable view clips to itself, not to the grid behind it
+        let mut dst = Grid::new(3, 3);
+        dst.view_mut([1, 1], 1, 1).unwrap().blit(&src, [-1, 0], BlendMode::Replace);
+        assert_eq!(dst.as_slice(), grid("...\n.O.\n...").as_slice());
+    }
+
+    #[test]
+    fn resize_pins_contents_to_the_anchor()
+    {
+        let g = grid("OO\nO.");
+        let anchors = [
+            (Anchor::TopLeft, "OO..\nO...\n....\n...."),
+            (Anchor::Top, ".OO.\n.O..\n....\n...."),
+            (Anchor::TopRight, "..OO\n..O.\n....\n...."),
+            (Anchor::Left, "....\nOO..\nO...\n...."),
+            (Anchor::Center, "....\n.OO.\n.O..\n...."),
+            (Anchor::Right, "....\n..OO\n..O.\n...."),
+            (Anchor::BottomLeft, "....\n....\nOO..\nO..."),
+            (Anchor::Bottom, "....\n....\n.OO.\n.O.."),
+            (Anchor::BottomRight, "....\n....\n..OO\n..O."),
+        ];
+
+        for (anchor, cells) in anchors
+        {
+            let grown = g.resize(4, 4, anchor, 0);
+            assert_eq!(grown.as_slice(), grid(cells).as_slice(), "{:?}", anchor);
+
+            // shrinking back keeps the same corner, so it undoes the growth
+            assert_eq!(grown.resize(2, 2, anchor, 0).as_slice(), g.as_slice(), "{:?}", anchor);
+        }
+
+        assert_eq!(g.resize(1, 3, Anchor::BottomRight, 5).as_slice(), [5, 1, 0]);
+    }
+
+    #[test]
+    fn pad_and_tile()
+    {
+        let g = grid("O.").with_boundary(Boundary::Wrap);
+
+        let padded = g.pad(1, 9);
+        assert_eq!(padded.as_slice(), [9, 9, 9, 9, 9, 1, 0, 9, 9, 9, 9, 9]);
+        assert_eq!(padded.boundary, Boundary::Wrap);
+        assert_eq!(g.pad(0, 9).as_slice(), g.as_slice());
+
+        let tiled = g.tile(3, 2);
+        assert_eq!((tiled.width, tiled.height), (6, 2));
+        assert_eq!(tiled.as_slice(), [1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0]);
+        assert_eq!(tiled.boundary, Boundary::Wrap);
+    }
+}